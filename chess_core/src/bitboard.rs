@@ -0,0 +1,78 @@
+use crate::board::{Piece, PieceKind, Square, Team};
+
+// the squares holding each team's pieces and each kind of piece, one bit per square, so the
+// occupancy of the board can be asked without looking at every square
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Bitboards {
+    teams: [u64; 2],
+    kinds: [u64; 6],
+}
+
+impl Bitboards {
+    pub fn occupied(&self) -> u64 {
+        self.teams[0] | self.teams[1]
+    }
+
+    pub fn team(&self, team: Team) -> u64 {
+        self.teams[team_index(team)]
+    }
+
+    pub fn kind(&self, kind: PieceKind) -> u64 {
+        self.kinds[kind_index(kind)]
+    }
+
+    pub fn pieces(&self, kind: PieceKind, team: Team) -> u64 {
+        self.kind(kind) & self.team(team)
+    }
+
+    // moves the bits of the square from the piece that was on it to the one now on it
+    pub(crate) fn replace(&mut self, square: Square, old: Option<Piece>, new: Option<Piece>) {
+        let bit = bit(square);
+
+        if let Some(piece) = old {
+            self.teams[team_index(piece.team)] &= !bit;
+            self.kinds[kind_index(piece.kind)] &= !bit;
+        }
+        if let Some(piece) = new {
+            self.teams[team_index(piece.team)] |= bit;
+            self.kinds[kind_index(piece.kind)] |= bit;
+        }
+    }
+}
+
+// the bit of the square, counting from white's bottom left corner rank by rank
+pub fn bit(square: Square) -> u64 {
+    1 << (square.y * 8 + square.x)
+}
+
+// the squares whose bits are set, from white's bottom left corner
+pub fn squares(mut bits: u64) -> impl Iterator<Item = Square> {
+    std::iter::from_fn(move || {
+        if bits == 0 {
+            return None;
+        }
+
+        let index = bits.trailing_zeros();
+        bits &= bits - 1;
+
+        Some(Square::new(index % 8, index / 8))
+    })
+}
+
+fn team_index(team: Team) -> usize {
+    match team {
+        Team::White => 0,
+        Team::Black => 1,
+    }
+}
+
+fn kind_index(kind: PieceKind) -> usize {
+    match kind {
+        PieceKind::Pawn => 0,
+        PieceKind::Rook => 1,
+        PieceKind::Knight => 2,
+        PieceKind::Bishop => 3,
+        PieceKind::Queen => 4,
+        PieceKind::King => 5,
+    }
+}
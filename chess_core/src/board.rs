@@ -0,0 +1,458 @@
+use std::fmt;
+
+use crate::{
+    bitboard::{squares, Bitboards},
+    fen::STARTING_FEN,
+};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Team {
+    White,
+    Black,
+}
+
+impl Team {
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::White => Self::Black,
+            Self::Black => Self::White,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PieceKind {
+    Pawn,
+    Rook,
+    Knight,
+    Bishop,
+    Queen,
+    King,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Piece {
+    pub kind: PieceKind,
+    pub team: Team,
+}
+
+impl Piece {
+    pub fn new(kind: PieceKind, team: Team) -> Self {
+        Self { kind, team }
+    }
+
+    // the letter the piece is written with, uppercase for white and lowercase for black
+    pub fn letter(self) -> char {
+        let letter = match self.kind {
+            PieceKind::Pawn => 'P',
+            PieceKind::Rook => 'R',
+            PieceKind::Knight => 'N',
+            PieceKind::Bishop => 'B',
+            PieceKind::Queen => 'Q',
+            PieceKind::King => 'K',
+        };
+
+        match self.team {
+            Team::White => letter,
+            Team::Black => letter.to_ascii_lowercase(),
+        }
+    }
+
+    // the piece written with the letter, the other way around of `letter`
+    pub fn from_letter(letter: char) -> Option<Self> {
+        let kind = match letter.to_ascii_uppercase() {
+            'P' => PieceKind::Pawn,
+            'R' => PieceKind::Rook,
+            'N' => PieceKind::Knight,
+            'B' => PieceKind::Bishop,
+            'Q' => PieceKind::Queen,
+            'K' => PieceKind::King,
+            _ => return None,
+        };
+        let team = match letter.is_ascii_uppercase() {
+            true => Team::White,
+            false => Team::Black,
+        };
+
+        Some(Self::new(kind, team))
+    }
+
+    // the unicode chess symbol of the piece
+    pub fn symbol(self) -> char {
+        match (self.team, self.kind) {
+            (Team::White, PieceKind::Pawn) => '♙',
+            (Team::White, PieceKind::Rook) => '♖',
+            (Team::White, PieceKind::Knight) => '♘',
+            (Team::White, PieceKind::Bishop) => '♗',
+            (Team::White, PieceKind::Queen) => '♕',
+            (Team::White, PieceKind::King) => '♔',
+            (Team::Black, PieceKind::Pawn) => '♟',
+            (Team::Black, PieceKind::Rook) => '♜',
+            (Team::Black, PieceKind::Knight) => '♞',
+            (Team::Black, PieceKind::Bishop) => '♝',
+            (Team::Black, PieceKind::Queen) => '♛',
+            (Team::Black, PieceKind::King) => '♚',
+        }
+    }
+}
+
+// a square of the board, x being the file and y the rank, both going from 0 to 7 starting at
+// white's bottom left corner
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Square {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl Square {
+    pub fn new(x: u32, y: u32) -> Self {
+        Self { x, y }
+    }
+
+    // the square reached by moving dx files and dy ranks, if it is still on the board
+    pub fn offset(self, dx: i32, dy: i32) -> Option<Self> {
+        let (x, y) = (self.x as i32 + dx, self.y as i32 + dy);
+
+        if (0..8).contains(&x) && (0..8).contains(&y) {
+            Some(Self::new(x as u32, y as u32))
+        } else {
+            None
+        }
+    }
+
+    // the square with the given name, like e4
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.as_bytes() {
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => {
+                Some(Self::new((file - b'a') as u32, (rank - b'1') as u32))
+            }
+            _ => None,
+        }
+    }
+
+    fn index(self) -> usize {
+        (self.y * 8 + self.x) as usize
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.x as u8) as char, self.y + 1)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Move {
+    pub from: Square,
+    pub to: Square,
+    // what a pawn reaching the last rank turns into
+    pub promotion: Option<PieceKind>,
+}
+
+impl Move {
+    pub fn new(from: Square, to: Square) -> Self {
+        Self {
+            from,
+            to,
+            promotion: None,
+        }
+    }
+
+    // the move written as engines speaking UCI do, the square moved from, the one moved to and
+    // the piece a pawn turns into, like e2e4 or e7e8q
+    pub fn from_uci(uci: &str) -> Option<Self> {
+        if !uci.is_ascii() || !(4..=5).contains(&uci.len()) {
+            return None;
+        }
+
+        let promotion = match uci.get(4..) {
+            Some("") => None,
+            Some("q") => Some(PieceKind::Queen),
+            Some("r") => Some(PieceKind::Rook),
+            Some("b") => Some(PieceKind::Bishop),
+            Some("n") => Some(PieceKind::Knight),
+            _ => return None,
+        };
+
+        Some(Self {
+            from: Square::parse(&uci[0..2])?,
+            to: Square::parse(&uci[2..4])?,
+            promotion,
+        })
+    }
+
+    // the move of the rook when the move is the king castling, two squares towards the rook
+    // which jumps over it
+    pub fn castling_rook(self, piece: Piece) -> Option<Move> {
+        if piece.kind != PieceKind::King || self.from.x.abs_diff(self.to.x) != 2 {
+            return None;
+        }
+
+        let rook_x = match self.to.x > self.from.x {
+            true => 7,
+            false => 0,
+        };
+
+        Some(Self::new(
+            Square::new(rook_x, self.from.y),
+            Square::new((self.from.x + self.to.x) / 2, self.from.y),
+        ))
+    }
+
+    pub fn to_uci(self) -> String {
+        let promotion = self
+            .promotion
+            .map(|kind| Piece::new(kind, Team::Black).letter().to_string())
+            .unwrap_or_default();
+
+        format!("{}{}{}", self.from, self.to, promotion)
+    }
+}
+
+// the sides each team can still castle to, kingside with the rook of the h file and queenside
+// with the one of the a file. a side is lost for good once the king or its rook moves
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub struct CastlingRights {
+    pub white_kingside: bool,
+    pub white_queenside: bool,
+    pub black_kingside: bool,
+    pub black_queenside: bool,
+}
+
+impl CastlingRights {
+    pub const ALL: Self = Self {
+        white_kingside: true,
+        white_queenside: true,
+        black_kingside: true,
+        black_queenside: true,
+    };
+
+    pub fn allows(self, team: Team, kingside: bool) -> bool {
+        match (team, kingside) {
+            (Team::White, true) => self.white_kingside,
+            (Team::White, false) => self.white_queenside,
+            (Team::Black, true) => self.black_kingside,
+            (Team::Black, false) => self.black_queenside,
+        }
+    }
+
+    // the rights left once a piece leaves or lands on the square, a king leaving its square loses
+    // both sides and a rook leaving its corner, or captured on it, loses its own
+    fn without(mut self, square: Square) -> Self {
+        match (square.x, square.y) {
+            (4, 0) => (self.white_kingside, self.white_queenside) = (false, false),
+            (4, 7) => (self.black_kingside, self.black_queenside) = (false, false),
+            (7, 0) => self.white_kingside = false,
+            (0, 0) => self.white_queenside = false,
+            (7, 7) => self.black_kingside = false,
+            (0, 7) => self.black_queenside = false,
+            _ => {}
+        }
+
+        self
+    }
+}
+
+// what a move changes on the board that the move itself doesn't tell, kept to take the move back
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Undo {
+    pub mv: Move,
+    // the piece that moved, the pawn when it was promoted
+    pub piece: Piece,
+    pub captured: Option<Piece>,
+    pub captured_square: Square,
+    pub en_passant: Option<Square>,
+    pub castling: CastlingRights,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Board {
+    squares: [Option<Piece>; 64],
+    // the same pieces as the squares, kept in sync by set_piece
+    bitboards: Bitboards,
+    // the square a pawn skipped advancing two squares in the last move, where it can be captured
+    // en passant
+    pub(crate) en_passant: Option<Square>,
+    pub(crate) castling: CastlingRights,
+    // the moves made, by either team, since the last capture or pawn move
+    pub(crate) halfmove_clock: u32,
+    // starts at 1 and goes up after every move of black, as games are written
+    pub(crate) fullmove_number: u32,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self {
+            squares: [None; 64],
+            bitboards: Bitboards::default(),
+            en_passant: None,
+            castling: CastlingRights::default(),
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        }
+    }
+}
+
+impl Board {
+    pub fn starting_position() -> Self {
+        let (board, _) = Self::from_fen(STARTING_FEN).expect("the starting FEN is valid");
+
+        board
+    }
+
+    pub fn piece_at(&self, square: Square) -> Option<Piece> {
+        self.squares[square.index()]
+    }
+
+    pub fn set_piece(&mut self, square: Square, piece: Option<Piece>) {
+        let old = self.piece_at(square);
+
+        self.squares[square.index()] = piece;
+        self.bitboards.replace(square, old, piece);
+    }
+
+    pub fn bitboards(&self) -> &Bitboards {
+        &self.bitboards
+    }
+
+    pub fn en_passant(&self) -> Option<Square> {
+        self.en_passant
+    }
+
+    pub fn castling(&self) -> CastlingRights {
+        self.castling
+    }
+
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    // where the piece the move captures stands, which isn't the square moved to when a pawn is
+    // captured en passant
+    pub fn captured_square(&self, mv: Move) -> Square {
+        let is_pawn = self
+            .piece_at(mv.from)
+            .is_some_and(|piece| piece.kind == PieceKind::Pawn);
+
+        match is_pawn && self.en_passant == Some(mv.to) {
+            true => Square::new(mv.to.x, mv.from.y),
+            false => mv.to,
+        }
+    }
+
+    // every piece on the board along with the square it stands on
+    pub fn pieces(&self) -> impl Iterator<Item = (Square, Piece)> + '_ {
+        squares(self.bitboards.occupied())
+            .filter_map(|square| self.piece_at(square).map(|piece| (square, piece)))
+    }
+
+    // a monospace drawing of the board seen from white's side, with the ranks and files labeled,
+    // using either letters or unicode symbols for the pieces
+    pub fn diagram(&self, unicode: bool) -> String {
+        let mut diagram = String::new();
+
+        for y in (0..8).rev() {
+            diagram.push_str(&format!("{} |", y + 1));
+
+            for x in 0..8 {
+                let c = match self.piece_at(Square::new(x, y)) {
+                    Some(piece) if unicode => piece.symbol(),
+                    Some(piece) => piece.letter(),
+                    None if unicode => '·',
+                    None => '.',
+                };
+
+                diagram.push(' ');
+                diagram.push(c);
+            }
+
+            diagram.push('\n');
+        }
+
+        diagram.push_str("  +----------------\n");
+        diagram.push_str("    a b c d e f g h\n");
+
+        diagram
+    }
+
+    // what's needed to take the move back once it's made, none if there's no piece to move
+    pub fn undo_for(&self, mv: Move) -> Option<Undo> {
+        let captured_square = self.captured_square(mv);
+
+        Some(Undo {
+            mv,
+            piece: self.piece_at(mv.from)?,
+            captured: self.piece_at(captured_square),
+            captured_square,
+            en_passant: self.en_passant,
+            castling: self.castling,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+        })
+    }
+
+    // takes back the last move made, putting the piece where it was and the captured one back
+    pub fn undo_move(&mut self, undo: Undo) {
+        self.set_piece(undo.mv.to, None);
+        self.set_piece(undo.captured_square, undo.captured);
+        self.set_piece(undo.mv.from, Some(undo.piece));
+        if let Some(rook) = undo.mv.castling_rook(undo.piece) {
+            let piece = self.piece_at(rook.to);
+
+            self.set_piece(rook.to, None);
+            self.set_piece(rook.from, piece);
+        }
+
+        self.en_passant = undo.en_passant;
+        self.castling = undo.castling;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.fullmove_number = undo.fullmove_number;
+    }
+
+    // moves the piece without checking the rules, returning the piece captured, if any
+    pub fn make_move(&mut self, mv: Move) -> Option<Piece> {
+        let piece = self.piece_at(mv.from);
+        let captured_square = self.captured_square(mv);
+        let captured = self.piece_at(captured_square);
+
+        self.set_piece(captured_square, None);
+        self.set_piece(mv.from, None);
+        self.set_piece(
+            mv.to,
+            piece.map(|piece| match mv.promotion {
+                Some(kind) => Piece::new(kind, piece.team),
+                None => piece,
+            }),
+        );
+
+        if let Some(rook) = piece.and_then(|piece| mv.castling_rook(piece)) {
+            let piece = self.piece_at(rook.from);
+
+            self.set_piece(rook.from, None);
+            self.set_piece(rook.to, piece);
+        }
+
+        // a pawn can only be taken en passant right after advancing two squares
+        let is_pawn = piece.is_some_and(|piece| piece.kind == PieceKind::Pawn);
+        self.en_passant = match is_pawn && mv.from.y.abs_diff(mv.to.y) == 2 {
+            true => Some(Square::new(mv.from.x, (mv.from.y + mv.to.y) / 2)),
+            false => None,
+        };
+        self.castling = self.castling.without(mv.from).without(mv.to);
+        self.halfmove_clock = match is_pawn || captured.is_some() {
+            true => 0,
+            false => self.halfmove_clock + 1,
+        };
+        if piece.is_some_and(|piece| piece.team == Team::Black) {
+            self.fullmove_number += 1;
+        }
+
+        captured
+    }
+}
@@ -0,0 +1,126 @@
+use crate::board::{Board, PieceKind, Square, Team};
+
+// what every piece is worth, in hundredths of a pawn. the king can't be traded, so it's only
+// worth what its square is
+const PAWN: i32 = 100;
+const KNIGHT: i32 = 320;
+const BISHOP: i32 = 330;
+const ROOK: i32 = 500;
+const QUEEN: i32 = 900;
+
+// how much better or worse every square is for a piece than the piece itself, as white sees the
+// board with the eighth rank on the first row. black's tables are the same ones flipped
+#[rustfmt::skip]
+const PAWN_SQUARES: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+#[rustfmt::skip]
+const KNIGHT_SQUARES: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+#[rustfmt::skip]
+const BISHOP_SQUARES: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+#[rustfmt::skip]
+const ROOK_SQUARES: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+#[rustfmt::skip]
+const QUEEN_SQUARES: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+// the king keeps behind its pawns, the middlegame is all the evaluation knows of
+#[rustfmt::skip]
+const KING_SQUARES: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+// what a piece of the kind is worth, without its square
+pub fn piece_value(kind: PieceKind) -> i32 {
+    match kind {
+        PieceKind::Pawn => PAWN,
+        PieceKind::Knight => KNIGHT,
+        PieceKind::Bishop => BISHOP,
+        PieceKind::Rook => ROOK,
+        PieceKind::Queen => QUEEN,
+        PieceKind::King => 0,
+    }
+}
+
+// how much the square adds to, or takes from, a piece of the team standing on it
+fn square_value(kind: PieceKind, team: Team, square: Square) -> i32 {
+    let table = match kind {
+        PieceKind::Pawn => &PAWN_SQUARES,
+        PieceKind::Knight => &KNIGHT_SQUARES,
+        PieceKind::Bishop => &BISHOP_SQUARES,
+        PieceKind::Rook => &ROOK_SQUARES,
+        PieceKind::Queen => &QUEEN_SQUARES,
+        PieceKind::King => &KING_SQUARES,
+    };
+    let row = match team {
+        Team::White => 7 - square.y,
+        Team::Black => square.y,
+    };
+
+    table[(row * 8 + square.x) as usize]
+}
+
+// how good the position is for the team, from the material of both teams and where it stands.
+// it's zero when both teams are as well off, and the same for the other team with the sign
+// flipped
+pub fn evaluate(board: &Board, team: Team) -> i32 {
+    board
+        .pieces()
+        .map(|(square, piece)| {
+            let value = piece_value(piece.kind) + square_value(piece.kind, piece.team, square);
+
+            match piece.team == team {
+                true => value,
+                false => -value,
+            }
+        })
+        .sum()
+}
@@ -0,0 +1,264 @@
+use std::fmt;
+
+use crate::{
+    board::{Board, CastlingRights, Piece, PieceKind, Square, Team},
+    rules::attacks::in_check,
+};
+
+// the position games start from
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+// why a FEN couldn't be read
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum FenError {
+    // it has less than the board and the team to move
+    MissingField(&'static str),
+    // a rank that isn't 8 squares long, or not 8 ranks
+    BadBoard,
+    UnknownPiece(char),
+    UnknownTeam(String),
+    BadCastling(String),
+    BadEnPassant(String),
+    BadCounter(String),
+    // the board can't come from a game: a team without a single king, a pawn on the first or
+    // last rank, or the team that just moved left in check
+    KingCount(Team, u32),
+    PawnOnBackRank(Square),
+    OpponentInCheck,
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingField(field) => write!(f, "the {} is missing", field),
+            Self::BadBoard => write!(f, "the board doesn't have 8 ranks of 8 squares"),
+            Self::UnknownPiece(c) => write!(f, "{} isn't a piece", c),
+            Self::UnknownTeam(team) => write!(f, "{} isn't a team, it should be w or b", team),
+            Self::BadCastling(castling) => write!(f, "{} isn't a castling right", castling),
+            Self::BadEnPassant(square) => write!(f, "{} isn't an en passant square", square),
+            Self::BadCounter(counter) => write!(f, "{} isn't a move count", counter),
+            Self::KingCount(team, count) => {
+                write!(f, "{:?} has {} kings instead of one", team, count)
+            }
+            Self::PawnOnBackRank(square) => write!(f, "the pawn on {} can't be there", square),
+            Self::OpponentInCheck => write!(f, "the team that just moved is in check"),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+impl Board {
+    // the board and the team to move written in the FEN, refused if the board couldn't be reached
+    // in a game. the fields after the team to move can be left out
+    pub fn from_fen(fen: &str) -> Result<(Self, Team), FenError> {
+        let mut fields = fen.split_whitespace();
+        let placement = fields.next().ok_or(FenError::MissingField("board"))?;
+        let to_move = match fields.next() {
+            Some("w") => Team::White,
+            Some("b") => Team::Black,
+            Some(team) => return Err(FenError::UnknownTeam(team.to_string())),
+            None => return Err(FenError::MissingField("team to move")),
+        };
+        let castling = match fields.next() {
+            None | Some("-") => CastlingRights::default(),
+            Some(letters) => {
+                parse_castling(letters).ok_or_else(|| FenError::BadCastling(letters.to_string()))?
+            }
+        };
+        // the square the pawn that just moved two squares went over, on the side of the team that
+        // moved it
+        let en_passant_rank = match to_move {
+            Team::White => 5,
+            Team::Black => 2,
+        };
+        let en_passant = match fields.next() {
+            None | Some("-") => None,
+            Some(name) => Some(
+                Square::parse(name)
+                    .filter(|square| square.y == en_passant_rank)
+                    .ok_or_else(|| FenError::BadEnPassant(name.to_string()))?,
+            ),
+        };
+        let mut counter = |default| match fields.next() {
+            Some(count) => count
+                .parse()
+                .map_err(|_| FenError::BadCounter(count.to_string())),
+            None => Ok(default),
+        };
+        let halfmove_clock = counter(0)?;
+        let fullmove_number = counter(1)?;
+
+        let mut board = Self::default();
+        let ranks: Vec<_> = placement.split('/').collect();
+        if ranks.len() != 8 {
+            return Err(FenError::BadBoard);
+        }
+
+        // the ranks are written from black's side of the board down
+        for (rank, row) in ranks.into_iter().enumerate() {
+            let y = 7 - rank as u32;
+            let mut x = 0;
+
+            for c in row.chars() {
+                if let Some(empty) = c.to_digit(10) {
+                    if !(1..=8).contains(&empty) {
+                        return Err(FenError::BadBoard);
+                    }
+                    x += empty;
+                    continue;
+                }
+                if x >= 8 {
+                    return Err(FenError::BadBoard);
+                }
+
+                let piece = Piece::from_letter(c).ok_or(FenError::UnknownPiece(c))?;
+                board.set_piece(Square::new(x, y), Some(piece));
+                x += 1;
+            }
+
+            if x != 8 {
+                return Err(FenError::BadBoard);
+            }
+        }
+
+        if let Some(square) = en_passant {
+            if !en_passant_possible(&board, to_move, square) {
+                return Err(FenError::BadEnPassant(square.to_string()));
+            }
+        }
+
+        board.castling = castling;
+        board.en_passant = en_passant;
+        board.halfmove_clock = halfmove_clock;
+        board.fullmove_number = fullmove_number;
+        check_position(&board, to_move)?;
+
+        Ok((board, to_move))
+    }
+
+    // the FEN of the board with the given team to move
+    pub fn to_fen(&self, to_move: Team) -> String {
+        let mut placement = Vec::new();
+
+        for y in (0..8).rev() {
+            let mut row = String::new();
+            let mut empty = 0;
+
+            for x in 0..8 {
+                match self.piece_at(Square::new(x, y)) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            row.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        row.push(piece.letter());
+                    }
+                    None => empty += 1,
+                }
+            }
+
+            if empty > 0 {
+                row.push_str(&empty.to_string());
+            }
+            placement.push(row);
+        }
+
+        let to_move = match to_move {
+            Team::White => "w",
+            Team::Black => "b",
+        };
+        let castling = [
+            (self.castling.white_kingside, 'K'),
+            (self.castling.white_queenside, 'Q'),
+            (self.castling.black_kingside, 'k'),
+            (self.castling.black_queenside, 'q'),
+        ]
+        .iter()
+        .filter(|(allowed, _)| *allowed)
+        .map(|(_, letter)| *letter)
+        .collect::<String>();
+        let en_passant = match self.en_passant {
+            Some(square) => square.to_string(),
+            None => "-".to_string(),
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            placement.join("/"),
+            to_move,
+            match castling.is_empty() {
+                true => "-",
+                false => castling.as_str(),
+            },
+            en_passant,
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+}
+
+// the rights written as some of KQkq, in that order and each at most once
+fn parse_castling(letters: &str) -> Option<CastlingRights> {
+    let mut rights = CastlingRights::default();
+    let mut rest = letters;
+
+    for (letter, right) in [
+        ('K', &mut rights.white_kingside),
+        ('Q', &mut rights.white_queenside),
+        ('k', &mut rights.black_kingside),
+        ('q', &mut rights.black_queenside),
+    ] {
+        if let Some(after) = rest.strip_prefix(letter) {
+            *right = true;
+            rest = after;
+        }
+    }
+
+    match rest.is_empty() && !letters.is_empty() {
+        true => Some(rights),
+        false => None,
+    }
+}
+
+// wether a pawn of the team that just moved can have gone over the square: it stands just
+// beyond it, and the square and the one it came from are empty
+fn en_passant_possible(board: &Board, to_move: Team, square: Square) -> bool {
+    let forward = match to_move {
+        Team::White => 1,
+        Team::Black => -1,
+    };
+    let pawn = Piece::new(PieceKind::Pawn, to_move.opposite());
+
+    board.piece_at(square).is_none()
+        && square
+            .offset(0, forward)
+            .is_some_and(|origin| board.piece_at(origin).is_none())
+        && square
+            .offset(0, -forward)
+            .and_then(|beyond| board.piece_at(beyond))
+            == Some(pawn)
+}
+
+// refuses the boards no game can reach
+fn check_position(board: &Board, to_move: Team) -> Result<(), FenError> {
+    for team in [Team::White, Team::Black] {
+        let kings = board.bitboards().pieces(PieceKind::King, team).count_ones();
+
+        if kings != 1 {
+            return Err(FenError::KingCount(team, kings));
+        }
+    }
+
+    if let Some((square, _)) = board
+        .pieces()
+        .find(|(square, piece)| piece.kind == PieceKind::Pawn && (square.y == 0 || square.y == 7))
+    {
+        return Err(FenError::PawnOnBackRank(square));
+    }
+
+    match in_check(board, to_move.opposite()) {
+        true => Err(FenError::OpponentInCheck),
+        false => Ok(()),
+    }
+}
@@ -0,0 +1,28 @@
+// the chess rules, kept free of any bevy type so every part of the game that needs to know what
+// is legal asks the same functions. it's a crate of its own so the rules can be tested, and used
+// by an engine or a server, without building bevy
+pub mod bitboard;
+pub mod board;
+pub mod eval;
+pub mod fen;
+pub mod perft;
+pub mod pgn;
+pub mod rules;
+pub mod san;
+pub mod search;
+#[cfg(all(debug_assertions, feature = "validate-moves"))]
+pub mod validation;
+
+pub use bitboard::Bitboards;
+pub use board::{Board, CastlingRights, Move, Piece, PieceKind, Square, Team, Undo};
+pub use eval::{evaluate, piece_value};
+pub use fen::{FenError, STARTING_FEN};
+pub use perft::perft;
+pub use pgn::{Pgn, PgnError};
+pub use rules::{
+    attacks::{in_check, is_attacked, king_square, pin_ray},
+    can_move, insufficient_material, is_legal, is_promotion, legal_moves, outcome, team_moves,
+    Outcome, PROMOTIONS,
+};
+pub use san::{parse_san, to_san};
+pub use search::{best_move, best_move_within, minimax, MATE};
@@ -0,0 +1,29 @@
+use crate::{
+    board::{Board, Team},
+    rules::team_moves,
+};
+
+// the number of move sequences `depth` moves long the team to move can start, counted to check
+// the move generator against the published counts of known positions
+pub fn perft(board: &Board, to_move: Team, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = team_moves(board, to_move);
+
+    // the leaves don't need to be played, only counted
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    moves
+        .into_iter()
+        .map(|mv| {
+            let mut after = board.clone();
+            after.make_move(mv);
+
+            perft(&after, to_move.opposite(), depth - 1)
+        })
+        .sum()
+}
@@ -0,0 +1,226 @@
+use std::fmt;
+
+use crate::{
+    board::{Board, Move, Team},
+    fen::{FenError, STARTING_FEN},
+    san::parse_san,
+};
+
+// the tokens ending the moves of a game
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+// a game read from PGN: its tags, its moves as they were written and how it ended. only the
+// first game of the text is read, and comments and variations are left out
+#[derive(Clone, Debug, Default)]
+pub struct Pgn {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<String>,
+    pub result: Option<String>,
+    // written after the last move, such as why the game ended. it's never read back
+    pub comment: Option<String>,
+}
+
+// why a game couldn't be read
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum PgnError {
+    BadTag(String),
+    Fen(FenError),
+    // the move, counted from the first one of both teams, that isn't legal or can't be read
+    IllegalMove { ply: usize, san: String },
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::BadTag(tag) => write!(f, "{} isn't a tag", tag),
+            Self::Fen(err) => write!(f, "the FEN tag can't be read: {}", err),
+            Self::IllegalMove { ply, san } => write!(
+                f,
+                "move {} ({}) isn't legal or can't be read",
+                ply / 2 + 1,
+                san
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+impl From<FenError> for PgnError {
+    fn from(err: FenError) -> Self {
+        Self::Fen(err)
+    }
+}
+
+impl Pgn {
+    pub fn parse(text: &str) -> Result<Self, PgnError> {
+        let mut pgn = Self::default();
+        let mut lines = text.lines().map(str::trim).peekable();
+
+        // the tags come first, one per line, until the moves start
+        while let Some(line) = lines.peek() {
+            if line.is_empty() {
+                lines.next();
+                continue;
+            }
+            if !line.starts_with('[') {
+                break;
+            }
+
+            let tag = line
+                .strip_prefix('[')
+                .and_then(|tag| tag.strip_suffix(']'))
+                .and_then(|tag| tag.split_once(' '))
+                .map(|(name, value)| (name, value.trim()))
+                .filter(|(_, value)| value.len() >= 2 && value.starts_with('"'))
+                .ok_or_else(|| PgnError::BadTag(line.to_string()))?;
+            let value = &tag.1[1..tag.1.len() - 1];
+
+            pgn.tags
+                .push((tag.0.to_string(), value.replace("\\\"", "\"")));
+            lines.next();
+        }
+
+        let movetext = strip_comments(&lines.collect::<Vec<_>>().join("\n"));
+        for token in movetext.split_whitespace() {
+            if RESULTS.contains(&token) {
+                pgn.result = Some(token.to_string());
+                break;
+            }
+            // annotations written as numbers
+            if token.starts_with('$') {
+                continue;
+            }
+
+            // the move numbers can be written right before the move, like 1.e4, they always end
+            // in a dot so castling written with zeros isn't taken for one
+            let san = match token
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .strip_prefix('.')
+            {
+                Some(san) => san.trim_start_matches('.'),
+                None => token,
+            };
+            if !san.is_empty() {
+                pgn.moves.push(san.to_string());
+            }
+        }
+
+        Ok(pgn)
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // the position the game starts from, the one of the FEN tag if there's one
+    pub fn starting_position(&self) -> Result<(Board, Team), PgnError> {
+        let fen = self.tag("FEN").unwrap_or(STARTING_FEN);
+
+        Ok(Board::from_fen(fen)?)
+    }
+
+    // the moves of the game, read from its starting position
+    pub fn play(&self) -> Result<Vec<Move>, PgnError> {
+        let (mut board, mut to_move) = self.starting_position()?;
+        let mut moves = Vec::new();
+
+        for (ply, san) in self.moves.iter().enumerate() {
+            let mv = parse_san(&board, to_move, san).ok_or_else(|| PgnError::IllegalMove {
+                ply,
+                san: san.clone(),
+            })?;
+
+            board.make_move(mv);
+            to_move = to_move.opposite();
+            moves.push(mv);
+        }
+
+        Ok(moves)
+    }
+}
+
+// the game written as PGN: its tags, then its numbered moves and result on lines that aren't
+// longer than 80 characters
+impl fmt::Display for Pgn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, value) in &self.tags {
+            writeln!(f, "[{} \"{}\"]", name, value.replace('"', "\\\""))?;
+        }
+        writeln!(f)?;
+
+        let (mut fullmove, mut to_move) = match self.starting_position() {
+            Ok((board, to_move)) => (board.fullmove_number(), to_move),
+            Err(_) => (1, Team::White),
+        };
+        let mut tokens = Vec::new();
+        for (ply, san) in self.moves.iter().enumerate() {
+            match to_move {
+                Team::White => tokens.push(format!("{}.", fullmove)),
+                // a game starting with black's move says which move it is
+                Team::Black if ply == 0 => tokens.push(format!("{}...", fullmove)),
+                Team::Black => {}
+            }
+            tokens.push(san.clone());
+
+            if to_move == Team::Black {
+                fullmove += 1;
+            }
+            to_move = to_move.opposite();
+        }
+        // a comment ends at the first closing brace, so the comment can't have one
+        if let Some(comment) = &self.comment {
+            tokens.push(format!("{{{}}}", comment.replace('}', ")")));
+        }
+        tokens.push(self.result.clone().unwrap_or_else(|| "*".to_string()));
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > 80 {
+                writeln!(f, "{}", line)?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+
+        writeln!(f, "{}", line)
+    }
+}
+
+// the movetext without its comments, in braces or after a semicolon, and its variations, in
+// parentheses that can be nested
+fn strip_comments(movetext: &str) -> String {
+    let mut stripped = String::new();
+    let mut chars = movetext.chars();
+    let mut depth = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                chars.by_ref().find(|c| *c == '}');
+                stripped.push(' ');
+            }
+            ';' => {
+                chars.by_ref().find(|c| *c == '\n');
+                stripped.push(' ');
+            }
+            '(' => depth += 1,
+            // a variation closed without being opened is left out, the moves after it are
+            // still read
+            ')' => {
+                depth = (depth - 1).max(0);
+                stripped.push(' ');
+            }
+            c if depth == 0 => stripped.push(c),
+            _ => {}
+        }
+    }
+
+    stripped
+}
@@ -0,0 +1,121 @@
+use super::{DIAGONAL_DIRECTIONS, KNIGHT_JUMPS, STRAIGHT_DIRECTIONS};
+use crate::{
+    bitboard::{bit, squares},
+    board::{Board, PieceKind, Square, Team},
+};
+
+// wether any piece of the given team could capture on the square, whatever stands on it
+pub fn is_attacked(board: &Board, square: Square, by: Team) -> bool {
+    let is_attacker = |square: Option<Square>, kinds: &[PieceKind]| {
+        square
+            .and_then(|square| board.piece_at(square))
+            .is_some_and(|piece| piece.team == by && kinds.contains(&piece.kind))
+    };
+
+    // a pawn attacks the squares diagonally in front of it, so it's found diagonally behind
+    let forward = match by {
+        Team::White => 1,
+        Team::Black => -1,
+    };
+    if [-1, 1]
+        .iter()
+        .any(|dx| is_attacker(square.offset(*dx, -forward), &[PieceKind::Pawn]))
+    {
+        return true;
+    }
+
+    let steps = |offsets: &[(i32, i32)], kinds: &[PieceKind]| {
+        offsets
+            .iter()
+            .any(|(dx, dy)| is_attacker(square.offset(*dx, *dy), kinds))
+    };
+    if steps(&KNIGHT_JUMPS, &[PieceKind::Knight])
+        || steps(&STRAIGHT_DIRECTIONS, &[PieceKind::King])
+        || steps(&DIAGONAL_DIRECTIONS, &[PieceKind::King])
+    {
+        return true;
+    }
+
+    let slides = |directions: &[(i32, i32)], kinds: &[PieceKind]| {
+        directions
+            .iter()
+            .any(|(dx, dy)| is_attacker(first_piece(board, square, *dx, *dy), kinds))
+    };
+
+    slides(&STRAIGHT_DIRECTIONS, &[PieceKind::Rook, PieceKind::Queen])
+        || slides(&DIAGONAL_DIRECTIONS, &[PieceKind::Bishop, PieceKind::Queen])
+}
+
+// the square of the team's king, if it's on the board
+pub fn king_square(board: &Board, team: Team) -> Option<Square> {
+    squares(board.bitboards().pieces(PieceKind::King, team)).next()
+}
+
+// wether the king of the team is attacked
+pub fn in_check(board: &Board, team: Team) -> bool {
+    king_square(board, team).is_some_and(|square| is_attacked(board, square, team.opposite()))
+}
+
+// the squares a piece pinned against its king can still move to without uncovering it, from the
+// king up to the enemy piece pinning it, or none if the piece isn't pinned
+pub fn pin_ray(board: &Board, square: Square) -> Option<Vec<Square>> {
+    let piece = board.piece_at(square)?;
+    if piece.kind == PieceKind::King {
+        return None;
+    }
+    let king = king_square(board, piece.team)?;
+    let (dx, dy) = direction(king, square)?;
+
+    // the piece has to be the only one between its king and an enemy sliding along the line
+    if first_piece(board, king, dx, dy) != Some(square) {
+        return None;
+    }
+    let pinner = first_piece(board, square, dx, dy)?;
+    let sliders = match dx == 0 || dy == 0 {
+        true => [PieceKind::Rook, PieceKind::Queen],
+        false => [PieceKind::Bishop, PieceKind::Queen],
+    };
+    let pinned = board
+        .piece_at(pinner)
+        .is_some_and(|p| p.team != piece.team && sliders.contains(&p.kind));
+    if !pinned {
+        return None;
+    }
+
+    let mut ray = Vec::new();
+    let mut current = king;
+    while current != pinner {
+        current = current.offset(dx, dy)?;
+        if current != square {
+            ray.push(current);
+        }
+    }
+
+    Some(ray)
+}
+
+// the step going from a square towards the other, if both are on the same file, rank or diagonal
+fn direction(from: Square, to: Square) -> Option<(i32, i32)> {
+    let (dx, dy) = (to.x as i32 - from.x as i32, to.y as i32 - from.y as i32);
+
+    if (dx, dy) == (0, 0) || (dx != 0 && dy != 0 && dx.abs() != dy.abs()) {
+        return None;
+    }
+
+    Some((dx.signum(), dy.signum()))
+}
+
+// the square of the first piece found going from the square in the direction, if any
+fn first_piece(board: &Board, square: Square, dx: i32, dy: i32) -> Option<Square> {
+    let occupied = board.bitboards().occupied();
+    let mut current = square;
+
+    while let Some(next) = current.offset(dx, dy) {
+        if occupied & bit(next) != 0 {
+            return Some(next);
+        }
+        current = next;
+    }
+
+    None
+}
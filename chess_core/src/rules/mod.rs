@@ -0,0 +1,336 @@
+pub mod attacks;
+
+use crate::{
+    bitboard::bit,
+    board::{Board, Move, Piece, PieceKind, Square, Team},
+};
+use attacks::{in_check, is_attacked, pin_ray};
+
+const STRAIGHT_DIRECTIONS: [(i32, i32); 4] = [(0, 1), (0, -1), (-1, 0), (1, 0)];
+const DIAGONAL_DIRECTIONS: [(i32, i32); 4] = [(-1, 1), (1, 1), (-1, -1), (1, -1)];
+const KNIGHT_JUMPS: [(i32, i32); 8] = [
+    (1, 2),
+    (-1, 2),
+    (1, -2),
+    (-1, -2),
+    (2, 1),
+    (2, -1),
+    (-2, 1),
+    (-2, -1),
+];
+// the moves of both teams without a capture or a pawn move that draw the game
+const FIFTY_MOVES: u32 = 100;
+// what a pawn can be promoted to
+pub const PROMOTIONS: [PieceKind; 4] = [
+    PieceKind::Queen,
+    PieceKind::Rook,
+    PieceKind::Bishop,
+    PieceKind::Knight,
+];
+
+// every move the piece standing on the given square can make, it's empty if there's no piece.
+// moves that would leave the king of the piece attacked aren't allowed, so a pinned piece can't
+// leave the pin and a king can't step into check
+pub fn legal_moves(board: &Board, square: Square) -> Vec<Move> {
+    let mut moves = pseudo_legal_moves(board, square);
+
+    // a pinned piece can only move along the pin, which is cheaper to tell than playing the move
+    if let Some(ray) = pin_ray(board, square) {
+        moves.retain(|mv| ray.contains(&mv.to));
+    }
+    moves.retain(|mv| !leaves_king_in_check(board, *mv));
+
+    moves
+}
+
+// every legal move of the team's pieces
+pub fn team_moves(board: &Board, team: Team) -> Vec<Move> {
+    board
+        .pieces()
+        .filter(|(_, piece)| piece.team == team)
+        .flat_map(|(square, _)| legal_moves(board, square))
+        .collect()
+}
+
+// the moves the piece can make as it moves, whatever happens to its king
+fn pseudo_legal_moves(board: &Board, square: Square) -> Vec<Move> {
+    let piece = match board.piece_at(square) {
+        Some(piece) => piece,
+        None => return Vec::new(),
+    };
+    let team = piece.team;
+    let mut targets = Vec::new();
+
+    match piece.kind {
+        PieceKind::Pawn => pawn_targets(board, square, team, &mut targets),
+        PieceKind::Rook => slide_targets(board, square, team, &STRAIGHT_DIRECTIONS, &mut targets),
+        PieceKind::Bishop => slide_targets(board, square, team, &DIAGONAL_DIRECTIONS, &mut targets),
+        PieceKind::Queen => {
+            slide_targets(board, square, team, &STRAIGHT_DIRECTIONS, &mut targets);
+            slide_targets(board, square, team, &DIAGONAL_DIRECTIONS, &mut targets);
+        }
+        PieceKind::Knight => step_targets(board, square, team, &KNIGHT_JUMPS, &mut targets),
+        PieceKind::King => {
+            step_targets(board, square, team, &STRAIGHT_DIRECTIONS, &mut targets);
+            step_targets(board, square, team, &DIAGONAL_DIRECTIONS, &mut targets);
+            castling_targets(board, square, team, &mut targets);
+        }
+    }
+
+    let mut moves = Vec::new();
+    for to in targets {
+        let mv = Move::new(square, to);
+
+        // a pawn reaching the last rank has to be promoted, there's a move for every piece it
+        // can turn into
+        match piece.kind == PieceKind::Pawn && (to.y == 0 || to.y == 7) {
+            true => moves.extend(PROMOTIONS.map(|kind| Move {
+                promotion: Some(kind),
+                ..mv
+            })),
+            false => moves.push(mv),
+        }
+    }
+
+    moves
+}
+
+pub fn is_legal(board: &Board, mv: Move) -> bool {
+    legal_moves(board, mv.from).contains(&mv)
+}
+
+// whether the move takes a pawn to the last rank, so a piece has to be picked for it
+pub fn is_promotion(board: &Board, mv: Move) -> bool {
+    legal_moves(board, mv.from)
+        .iter()
+        .any(|legal| legal.to == mv.to && legal.promotion.is_some())
+}
+
+// how a game ended
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Outcome {
+    Checkmate { winner: Team },
+    Stalemate,
+    // fifty moves by each team without a capture or a pawn move
+    FiftyMoveRule,
+    // neither team has the pieces left to give mate
+    InsufficientMaterial,
+    // the players ended the game themselves, these never come from the position
+    Resignation { winner: Team },
+    DrawAgreed,
+}
+
+impl Outcome {
+    // the team that won, none if the game is drawn
+    pub fn winner(self) -> Option<Team> {
+        match self {
+            Self::Checkmate { winner } | Self::Resignation { winner } => Some(winner),
+            _ => None,
+        }
+    }
+
+    // the result as PGN writes it, with white's score first
+    pub fn result(self) -> &'static str {
+        match self.winner() {
+            Some(Team::White) => "1-0",
+            Some(Team::Black) => "0-1",
+            None => "1/2-1/2",
+        }
+    }
+}
+
+// whether the team has any legal move left
+pub fn can_move(board: &Board, team: Team) -> bool {
+    board
+        .pieces()
+        .filter(|(_, piece)| piece.team == team)
+        .any(|(square, _)| !legal_moves(board, square).is_empty())
+}
+
+// how the game ends if the team to move can't move anymore, or none if the game goes on. a mate
+// given on the last of the fifty moves still wins the game
+pub fn outcome(board: &Board, to_move: Team) -> Option<Outcome> {
+    if can_move(board, to_move) {
+        if insufficient_material(board) {
+            Some(Outcome::InsufficientMaterial)
+        } else if board.halfmove_clock() >= FIFTY_MOVES {
+            Some(Outcome::FiftyMoveRule)
+        } else {
+            None
+        }
+    } else if in_check(board, to_move) {
+        Some(Outcome::Checkmate {
+            winner: to_move.opposite(),
+        })
+    } else {
+        Some(Outcome::Stalemate)
+    }
+}
+
+// whether no sequence of moves can end in a mate: only the kings are left, with at most a single
+// knight or bishop, or bishops that all stand on squares of the same color
+pub fn insufficient_material(board: &Board) -> bool {
+    let bitboards = board.bitboards();
+    let heavy = [PieceKind::Pawn, PieceKind::Rook, PieceKind::Queen];
+    if heavy.iter().any(|kind| bitboards.kind(*kind) != 0) {
+        return false;
+    }
+
+    let mut knights = 0;
+    let mut bishop_colors = Vec::new();
+
+    // only kings and minor pieces are left
+    for (square, piece) in board.pieces() {
+        match piece.kind {
+            PieceKind::Knight => knights += 1,
+            PieceKind::Bishop => bishop_colors.push((square.x + square.y) % 2),
+            _ => {}
+        }
+    }
+
+    match knights {
+        0 => bishop_colors.windows(2).all(|pair| pair[0] == pair[1]),
+        1 => bishop_colors.is_empty(),
+        _ => false,
+    }
+}
+
+// plays the move on a copy of the board to see if the king of the team moving ends up attacked
+fn leaves_king_in_check(board: &Board, mv: Move) -> bool {
+    let team = match board.piece_at(mv.from) {
+        Some(piece) => piece.team,
+        None => return false,
+    };
+    let mut after = board.clone();
+    after.make_move(mv);
+
+    in_check(&after, team)
+}
+
+// whether a piece of the given team can end its move on the square, that is, the square is
+// empty or holds an enemy piece
+fn can_land_on(board: &Board, square: Square, team: Team) -> bool {
+    match board.piece_at(square) {
+        Some(piece) => piece.team != team,
+        None => true,
+    }
+}
+
+fn pawn_targets(board: &Board, square: Square, team: Team, targets: &mut Vec<Square>) {
+    let (forward, start_rank) = match team {
+        Team::White => (1, 1),
+        Team::Black => (-1, 6),
+    };
+
+    // forward moves never capture, so they need every square they go through to be empty
+    if let Some(front) = square.offset(0, forward) {
+        if board.piece_at(front).is_none() {
+            targets.push(front);
+
+            // the two squares advance is only allowed from the start rank
+            if square.y == start_rank {
+                if let Some(next_front) = front.offset(0, forward) {
+                    if board.piece_at(next_front).is_none() {
+                        targets.push(next_front);
+                    }
+                }
+            }
+        }
+    }
+
+    // diagonal moves are only allowed to capture an enemy piece, which can be the pawn that just
+    // skipped the square
+    for dx in [-1, 1] {
+        if let Some(diagonal) = square.offset(dx, forward) {
+            let captures = match board.piece_at(diagonal) {
+                Some(piece) => piece.team != team,
+                None => board.en_passant() == Some(diagonal),
+            };
+
+            if captures {
+                targets.push(diagonal);
+            }
+        }
+    }
+}
+
+// moves in every direction given until the edge of the board or a piece is reached, the piece
+// can be captured only if it's an enemy
+fn slide_targets(
+    board: &Board,
+    square: Square,
+    team: Team,
+    directions: &[(i32, i32)],
+    targets: &mut Vec<Square>,
+) {
+    let (occupied, own) = (board.bitboards().occupied(), board.bitboards().team(team));
+
+    for (dx, dy) in directions {
+        let mut current = square;
+
+        while let Some(next) = current.offset(*dx, *dy) {
+            if own & bit(next) == 0 {
+                targets.push(next);
+            }
+
+            if occupied & bit(next) != 0 {
+                break;
+            }
+
+            current = next;
+        }
+    }
+}
+
+// the squares two files away the king castles to, towards a rook of its corner when the team still
+// can castle to that side. every square between the king and the rook has to be empty, and the
+// king can't be in check, pass through an attacked square or land on one
+fn castling_targets(board: &Board, square: Square, team: Team, targets: &mut Vec<Square>) {
+    let rank = match team {
+        Team::White => 0,
+        Team::Black => 7,
+    };
+    if square != Square::new(4, rank) || is_attacked(board, square, team.opposite()) {
+        return;
+    }
+
+    for (kingside, rook_x, between, step) in [(true, 7, 5..7, 1), (false, 0, 1..4, -1)] {
+        let rook = Some(Piece::new(PieceKind::Rook, team));
+        let empty = between
+            .into_iter()
+            .all(|x| board.piece_at(Square::new(x, rank)).is_none());
+        if !board.castling().allows(team, kingside)
+            || board.piece_at(Square::new(rook_x, rank)) != rook
+            || !empty
+        {
+            continue;
+        }
+
+        let (passed, landed) = match (square.offset(step, 0), square.offset(2 * step, 0)) {
+            (Some(passed), Some(landed)) => (passed, landed),
+            _ => continue,
+        };
+        if !is_attacked(board, passed, team.opposite())
+            && !is_attacked(board, landed, team.opposite())
+        {
+            targets.push(landed);
+        }
+    }
+}
+
+// moves a single time by every offset given
+fn step_targets(
+    board: &Board,
+    square: Square,
+    team: Team,
+    offsets: &[(i32, i32)],
+    targets: &mut Vec<Square>,
+) {
+    for (dx, dy) in offsets {
+        if let Some(next) = square.offset(*dx, *dy) {
+            if can_land_on(board, next, team) {
+                targets.push(next);
+            }
+        }
+    }
+}
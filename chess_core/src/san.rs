@@ -0,0 +1,166 @@
+use crate::{
+    board::{Board, Move, Piece, PieceKind, Square, Team},
+    rules::{attacks::in_check, can_move, team_moves},
+};
+
+// the move, legal on the board it's about to be made on, written in standard algebraic
+// notation. the file, the rank or both of the square moved from are written when another piece of
+// the same kind could move there too, and castling is written O-O or O-O-O
+pub fn to_san(board: &Board, mv: Move) -> String {
+    let piece = match board.piece_at(mv.from) {
+        Some(piece) => piece,
+        None => return String::new(),
+    };
+    // castling is told by the side the king goes to, not by the square
+    let mut san = match mv.castling_rook(piece) {
+        Some(rook) if rook.from.x > mv.from.x => "O-O".to_string(),
+        Some(_) => "O-O-O".to_string(),
+        None => piece_move(board, mv, piece),
+    };
+
+    let mut after = board.clone();
+    after.make_move(mv);
+    let opponent = piece.team.opposite();
+    if in_check(&after, opponent) {
+        match can_move(&after, opponent) {
+            true => san.push('+'),
+            false => san.push('#'),
+        }
+    }
+
+    san
+}
+
+// the legal move written in standard algebraic notation, like Nbd7, exd6 or e8=Q+. the check and
+// annotation marks are ignored
+pub fn parse_san(board: &Board, to_move: Team, san: &str) -> Option<Move> {
+    let san = san.trim_end_matches(['+', '#', '!', '?']);
+
+    // castling is written with the letter O, or the digit 0 by some
+    let castling = match san {
+        "O-O" | "0-0" => Some(true),
+        "O-O-O" | "0-0-0" => Some(false),
+        _ => None,
+    };
+    if let Some(kingside) = castling {
+        return team_moves(board, to_move).into_iter().find(|mv| {
+            board
+                .piece_at(mv.from)
+                .and_then(|piece| mv.castling_rook(piece))
+                .is_some_and(|rook| (rook.from.x > mv.from.x) == kingside)
+        });
+    }
+
+    let (kind, rest) = match san.chars().next()? {
+        'K' => (PieceKind::King, &san[1..]),
+        'Q' => (PieceKind::Queen, &san[1..]),
+        'R' => (PieceKind::Rook, &san[1..]),
+        'B' => (PieceKind::Bishop, &san[1..]),
+        'N' => (PieceKind::Knight, &san[1..]),
+        _ => (PieceKind::Pawn, san),
+    };
+
+    // the piece a pawn turns into comes last, after an = or right after the square
+    let (rest, promotion) = match rest.char_indices().last()? {
+        (i, c) if kind == PieceKind::Pawn && c.is_ascii_uppercase() => {
+            let kind = match c {
+                'Q' => PieceKind::Queen,
+                'R' => PieceKind::Rook,
+                'B' => PieceKind::Bishop,
+                'N' => PieceKind::Knight,
+                _ => return None,
+            };
+
+            (rest[..i].trim_end_matches('='), Some(kind))
+        }
+        _ => (rest, None),
+    };
+
+    let rest: String = rest.chars().filter(|c| *c != 'x').collect();
+    if rest.len() < 2 || !rest.is_ascii() {
+        return None;
+    }
+    let (from_hint, to) = rest.split_at(rest.len() - 2);
+    let to = Square::parse(to)?;
+
+    // the file, the rank or both the piece moves from, when another piece could move there too
+    let mut file = None;
+    let mut rank = None;
+    for c in from_hint.chars() {
+        match c {
+            'a'..='h' => file = Some(c as u32 - 'a' as u32),
+            '1'..='8' => rank = Some(c as u32 - '1' as u32),
+            _ => return None,
+        }
+    }
+
+    let mut candidates = team_moves(board, to_move).into_iter().filter(|mv| {
+        mv.to == to
+            && mv.promotion == promotion
+            && file.is_none_or(|file| mv.from.x == file)
+            && rank.is_none_or(|rank| mv.from.y == rank)
+            && board
+                .piece_at(mv.from)
+                .is_some_and(|piece| piece.kind == kind)
+    });
+
+    // a move matching more than one piece is ambiguous
+    match (candidates.next(), candidates.next()) {
+        (Some(mv), None) => Some(mv),
+        _ => None,
+    }
+}
+
+// the piece, the square it moves to and what tells it apart from the other pieces that could go
+// there, everything but the check or mate mark
+fn piece_move(board: &Board, mv: Move, piece: Piece) -> String {
+    let capture = board.piece_at(board.captured_square(mv)).is_some();
+    let mut san = String::new();
+
+    match piece.kind {
+        PieceKind::Pawn => {
+            // a pawn capturing is told by the file it comes from
+            if capture {
+                san.push(file_letter(mv.from));
+            }
+        }
+        kind => {
+            san.push(Piece::new(kind, Team::White).letter());
+
+            let rivals: Vec<_> = team_moves(board, piece.team)
+                .into_iter()
+                .filter(|other| {
+                    other.to == mv.to
+                        && other.from != mv.from
+                        && board.piece_at(other.from) == Some(piece)
+                })
+                .collect();
+            if !rivals.is_empty() {
+                let same_file = rivals.iter().any(|other| other.from.x == mv.from.x);
+                let same_rank = rivals.iter().any(|other| other.from.y == mv.from.y);
+
+                if !same_file || same_rank {
+                    san.push(file_letter(mv.from));
+                }
+                if same_file {
+                    san.push_str(&(mv.from.y + 1).to_string());
+                }
+            }
+        }
+    }
+
+    if capture {
+        san.push('x');
+    }
+    san.push_str(&mv.to.to_string());
+    if let Some(kind) = mv.promotion {
+        san.push('=');
+        san.push(Piece::new(kind, Team::White).letter());
+    }
+
+    san
+}
+
+fn file_letter(square: Square) -> char {
+    (b'a' + square.x as u8) as char
+}
@@ -0,0 +1,225 @@
+use std::{
+    cmp::Reverse,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    board::{Board, Move, PieceKind, Team},
+    eval::{evaluate, piece_value},
+    rules::{attacks::in_check, team_moves},
+};
+
+// the score of a mate, above anything the material on the board adds up to. mates found sooner
+// score higher, so the quickest one is played
+pub const MATE: i32 = 1_000_000;
+// above any score, mates included
+const INFINITY: i32 = MATE + 1;
+// how deep iterative deepening goes when the time budget doesn't stop it first
+const MAX_DEPTH: u32 = 64;
+// how many positions are searched between two looks at the clock
+const CLOCK_CHECK: u64 = 1024;
+
+// the move the search likes best for the team, looking the given number of moves of both teams
+// ahead, and every capture after them. none if the team can't move
+pub fn best_move(board: &Board, to_move: Team, depth: u32) -> Option<Move> {
+    Search::new(None)
+        .root(board, to_move, depth.max(1), None)
+        .map(|(mv, _)| mv)
+}
+
+// the best move found by searching one move deeper at a time until the time is up, the deepest
+// search finished has the last word. the first one is always finished, so a team that can move
+// always gets a move, however little time it's given
+pub fn best_move_within(board: &Board, to_move: Team, budget: Duration) -> Option<Move> {
+    let deadline = Instant::now() + budget;
+    let mut best = None;
+
+    for depth in 1..=MAX_DEPTH {
+        let mut search = Search::new((depth > 1).then_some(deadline));
+        let (mv, score) = match search.root(board, to_move, depth, best) {
+            Some(found) => found,
+            None => break,
+        };
+
+        best = Some(mv);
+        // nothing deeper changes a mate found
+        if score.abs() > MATE - MAX_DEPTH as i32 || Instant::now() >= deadline {
+            break;
+        }
+    }
+
+    best
+}
+
+// the score of the position for the team to move, when both teams play the best moves they can
+// find in the given number of moves, each team's score being the other's with the sign flipped
+pub fn minimax(board: &Board, to_move: Team, depth: u32, ply: i32) -> i32 {
+    Search::new(None).alpha_beta(board, to_move, depth, ply, -INFINITY, INFINITY)
+}
+
+struct Search {
+    deadline: Option<Instant>,
+    nodes: u64,
+    stopped: bool,
+}
+
+impl Search {
+    fn new(deadline: Option<Instant>) -> Self {
+        Self {
+            deadline,
+            nodes: 0,
+            stopped: false,
+        }
+    }
+
+    // whether the search has to stop, the clock is only looked at every few positions
+    fn out_of_time(&mut self) -> bool {
+        self.nodes += 1;
+
+        if let (false, Some(deadline)) = (self.stopped, self.deadline) {
+            if self.nodes.is_multiple_of(CLOCK_CHECK) {
+                self.stopped = Instant::now() >= deadline;
+            }
+        }
+
+        self.stopped
+    }
+
+    // the best move and its score, searching the given move first. none if the team can't move
+    // or the time was up before every move was searched
+    fn root(
+        &mut self,
+        board: &Board,
+        to_move: Team,
+        depth: u32,
+        first: Option<Move>,
+    ) -> Option<(Move, i32)> {
+        let mut moves = team_moves(board, to_move);
+        order_moves(board, &mut moves);
+        if let Some(index) = first.and_then(|first| moves.iter().position(|mv| *mv == first)) {
+            moves[..=index].rotate_right(1);
+        }
+
+        let mut best = None;
+        let mut alpha = -INFINITY;
+        for mv in moves {
+            let mut after = board.clone();
+            after.make_move(mv);
+            let score =
+                -self.alpha_beta(&after, to_move.opposite(), depth - 1, 1, -INFINITY, -alpha);
+
+            if self.stopped {
+                return None;
+            }
+            if score > alpha {
+                alpha = score;
+                best = Some((mv, score));
+            }
+        }
+
+        best
+    }
+
+    // the score of the position between alpha, what the team to move is already sure of, and
+    // beta, what the other team already keeps it under. moves that can't do better than alpha,
+    // and positions the other team won't let happen, aren't searched any further
+    fn alpha_beta(
+        &mut self,
+        board: &Board,
+        to_move: Team,
+        depth: u32,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        if self.out_of_time() {
+            return 0;
+        }
+        if depth == 0 {
+            return self.quiescence(board, to_move, alpha, beta);
+        }
+
+        let mut moves = team_moves(board, to_move);
+        if moves.is_empty() {
+            return match in_check(board, to_move) {
+                true => -(MATE - ply),
+                false => 0,
+            };
+        }
+
+        order_moves(board, &mut moves);
+        for mv in moves {
+            let mut after = board.clone();
+            after.make_move(mv);
+            let score = -self.alpha_beta(
+                &after,
+                to_move.opposite(),
+                depth - 1,
+                ply + 1,
+                -beta,
+                -alpha,
+            );
+
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+
+        alpha
+    }
+
+    // keeps searching the captures and queen promotions once the depth is reached, so a piece
+    // isn't taken for free when it's defended, or left hanging on the last move searched. the
+    // team to move can stop capturing if it's better off as it stands
+    fn quiescence(&mut self, board: &Board, to_move: Team, mut alpha: i32, beta: i32) -> i32 {
+        if self.out_of_time() {
+            return 0;
+        }
+
+        let standing = evaluate(board, to_move);
+        if standing >= beta {
+            return beta;
+        }
+        alpha = alpha.max(standing);
+
+        let mut moves: Vec<_> = team_moves(board, to_move)
+            .into_iter()
+            .filter(|mv| is_noisy(board, *mv))
+            .collect();
+        order_moves(board, &mut moves);
+        for mv in moves {
+            let mut after = board.clone();
+            after.make_move(mv);
+            let score = -self.quiescence(&after, to_move.opposite(), -beta, -alpha);
+
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+
+        alpha
+    }
+}
+
+fn is_noisy(board: &Board, mv: Move) -> bool {
+    board.piece_at(board.captured_square(mv)).is_some() || mv.promotion == Some(PieceKind::Queen)
+}
+
+// the captures go first, the most valuable pieces taken with the least valuable ones before the
+// rest, as they're the moves most likely to leave the other ones unsearched
+fn order_moves(board: &Board, moves: &mut [Move]) {
+    moves.sort_by_key(|mv| {
+        let victim = board.piece_at(board.captured_square(*mv));
+        let attacker = board
+            .piece_at(mv.from)
+            .map_or(0, |piece| piece_value(piece.kind));
+        let promotion = mv.promotion.map_or(0, piece_value);
+
+        Reverse(match victim {
+            Some(victim) => 10 * piece_value(victim.kind) - attacker + promotion,
+            None => promotion,
+        })
+    });
+}
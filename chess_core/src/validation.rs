@@ -0,0 +1,153 @@
+// cross-checks the in-house rules against the shakmaty crate, only compiled with the
+// `validate-moves` feature on debug builds, panicking with the position written as a FEN as soon
+// as both disagree
+use shakmaty::{
+    fen::Fen, CastlingMode, Chess, Color, FromSetup, Position, PositionError, Role, Setup,
+};
+
+use crate::board::{Board, Move, PieceKind, Square, Team};
+
+fn to_shakmaty_square(square: Square) -> shakmaty::Square {
+    shakmaty::Square::new(square.y * 8 + square.x)
+}
+
+fn from_shakmaty_square(square: shakmaty::Square) -> Square {
+    let index = u32::from(square);
+
+    Square::new(index % 8, index / 8)
+}
+
+fn to_shakmaty_setup(board: &Board, turn: Team) -> Setup {
+    let mut setup = Setup::empty();
+
+    for (square, piece) in board.pieces() {
+        setup.board.set_piece_at(
+            to_shakmaty_square(square),
+            shakmaty::Piece {
+                color: to_shakmaty_color(piece.team),
+                role: to_shakmaty_role(piece.kind),
+            },
+        );
+    }
+    setup.turn = to_shakmaty_color(turn);
+    let castling = board.castling();
+    setup.castling_rights = [
+        (castling.white_kingside, shakmaty::Square::H1),
+        (castling.white_queenside, shakmaty::Square::A1),
+        (castling.black_kingside, shakmaty::Square::H8),
+        (castling.black_queenside, shakmaty::Square::A8),
+    ]
+    .into_iter()
+    .filter(|(allowed, _)| *allowed)
+    .map(|(_, square)| square)
+    .collect();
+    setup.ep_square = board.en_passant().map(to_shakmaty_square);
+    setup.halfmoves = board.halfmove_clock();
+
+    setup
+}
+
+fn to_shakmaty_role(kind: PieceKind) -> Role {
+    match kind {
+        PieceKind::Pawn => Role::Pawn,
+        PieceKind::Rook => Role::Rook,
+        PieceKind::Knight => Role::Knight,
+        PieceKind::Bishop => Role::Bishop,
+        PieceKind::Queen => Role::Queen,
+        PieceKind::King => Role::King,
+    }
+}
+
+fn to_shakmaty_color(team: Team) -> Color {
+    match team {
+        Team::White => Color::White,
+        Team::Black => Color::Black,
+    }
+}
+
+// the position shakmaty sees, with the team of the piece on the square to move
+fn reference_position(board: &Board, square: Square) -> Option<(Chess, String)> {
+    let piece = board.piece_at(square)?;
+    let setup = to_shakmaty_setup(board, piece.team);
+    let fen = Fen::from_setup(setup.clone()).to_string();
+
+    // a right kept without its king or rook at home is never used, the rules don't see it either
+    match Chess::from_setup(setup, CastlingMode::Standard)
+        .or_else(PositionError::ignore_invalid_castling_rights)
+    {
+        Ok(position) => Some((position, fen)),
+        Err(e) => panic!("The position {} isn't valid: {}", fen, e),
+    }
+}
+
+// where the king lands when the move castles, shakmaty has castling go to the rook
+fn king_target(position: &Chess, m: &shakmaty::Move) -> shakmaty::Square {
+    match m.castling_side() {
+        Some(side) => side.king_to(position.turn()),
+        None => m.to(),
+    }
+}
+
+fn sorted_targets(moves: impl Iterator<Item = Square>) -> Vec<(u32, u32)> {
+    let mut targets: Vec<(u32, u32)> = moves.map(|square| (square.y, square.x)).collect();
+
+    // promotions are a move for every piece, only where the piece goes is compared
+    targets.sort_unstable();
+    targets.dedup();
+
+    targets
+}
+
+// panics if the moves generated for the piece on the square aren't the ones shakmaty generates
+pub fn validate_legal_moves(board: &Board, square: Square, moves: &[Move]) {
+    let (position, fen) = match reference_position(board, square) {
+        Some(reference) => reference,
+        None => return,
+    };
+    let from = to_shakmaty_square(square);
+    let expected = sorted_targets(
+        position
+            .legal_moves()
+            .iter()
+            .filter(|m| m.from() == Some(from))
+            .map(|m| from_shakmaty_square(king_target(&position, m))),
+    );
+    let generated = sorted_targets(moves.iter().map(|mv| mv.to));
+
+    if expected != generated {
+        panic!(
+            "The moves from {:?} diverge from shakmaty in {}: generated {:?}, expected {:?}",
+            square, fen, generated, expected
+        );
+    }
+}
+
+// panics if playing the move on the board before it doesn't give the same board as shakmaty
+pub fn validate_move(before: &Board, mv: Move, after: &Board) {
+    let (mut position, fen) = match reference_position(before, mv.from) {
+        Some(reference) => reference,
+        None => return,
+    };
+    let (from, to) = (to_shakmaty_square(mv.from), to_shakmaty_square(mv.to));
+    let reference_move = position.legal_moves().into_iter().find(|m| {
+        m.from() == Some(from)
+            && king_target(&position, m) == to
+            && m.promotion() == mv.promotion.map(to_shakmaty_role)
+    });
+
+    match reference_move {
+        Some(m) => position.play_unchecked(&m),
+        None => panic!("The move {:?} isn't legal for shakmaty in {}", mv, fen),
+    }
+
+    let played = to_shakmaty_setup(after, Team::White).board;
+    if *position.board() != played {
+        panic!(
+            "Playing {:?} in {} diverges from shakmaty: got {}, expected {}",
+            mv,
+            fen,
+            played,
+            position.board()
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use chess_core::*;
+
+#[test]
+fn starting_position_round_trips() {
+    let (board, to_move) = Board::from_fen(STARTING_FEN).unwrap();
+
+    assert_eq!(to_move, Team::White);
+    assert_eq!(
+        board.piece_at(Square::new(4, 0)),
+        Some(Piece::new(PieceKind::King, Team::White))
+    );
+    assert_eq!(
+        board.piece_at(Square::new(3, 7)),
+        Some(Piece::new(PieceKind::Queen, Team::Black))
+    );
+    assert_eq!(board.to_fen(to_move), STARTING_FEN);
+}
+
+#[test]
+fn fen_keeps_en_passant_and_move_counters() {
+    let mut board = Board::starting_position();
+    board.make_move(Move::new(Square::new(4, 1), Square::new(4, 3)));
+
+    assert_eq!(
+        board.to_fen(Team::Black),
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+    );
+
+    board.make_move(Move::new(Square::new(6, 7), Square::new(5, 5)));
+
+    assert_eq!(
+        board.to_fen(Team::White),
+        "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2"
+    );
+}
+
+#[test]
+fn fen_without_move_counters_is_read() {
+    let (board, to_move) = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6").unwrap();
+
+    assert_eq!(to_move, Team::White);
+    assert_eq!(board.en_passant(), Some(Square::new(3, 5)));
+    assert_eq!(board.halfmove_clock(), 0);
+    assert_eq!(board.fullmove_number(), 1);
+}
+
+#[test]
+fn malformed_fen_is_refused() {
+    assert_eq!(
+        Board::from_fen("").unwrap_err(),
+        FenError::MissingField("board")
+    );
+    assert_eq!(
+        Board::from_fen("8/8/8/8/8/8/8/8").unwrap_err(),
+        FenError::MissingField("team to move")
+    );
+    assert_eq!(
+        Board::from_fen("8/8/8/8/8/8/8 w").unwrap_err(),
+        FenError::BadBoard
+    );
+    assert_eq!(
+        Board::from_fen("9/8/8/8/8/8/8/8 w").unwrap_err(),
+        FenError::BadBoard
+    );
+    assert_eq!(
+        Board::from_fen("08/8/8/8/8/8/8/8 w").unwrap_err(),
+        FenError::BadBoard
+    );
+    assert_eq!(
+        Board::from_fen("x7/8/8/8/8/8/8/8 w").unwrap_err(),
+        FenError::UnknownPiece('x')
+    );
+    assert_eq!(
+        Board::from_fen("8/8/8/8/8/8/8/8 x").unwrap_err(),
+        FenError::UnknownTeam("x".to_string())
+    );
+    assert_eq!(
+        Board::from_fen("8/8/8/8/8/8/8/8 w - e4").unwrap_err(),
+        FenError::BadEnPassant("e4".to_string())
+    );
+    // the square is on the side of the team that just moved, behind one of its pawns
+    assert_eq!(
+        Board::from_fen("4k3/8/8/8/8/8/3PP3/4K3 w - e3 0 1").unwrap_err(),
+        FenError::BadEnPassant("e3".to_string())
+    );
+    assert_eq!(
+        Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 w - e6 0 1").unwrap_err(),
+        FenError::BadEnPassant("e6".to_string())
+    );
+    assert_eq!(
+        Board::from_fen("4k3/8/8/8/3pP3/8/4N3/4K3 b - e3 0 1").unwrap_err(),
+        FenError::BadEnPassant("e3".to_string())
+    );
+    assert_eq!(
+        Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1")
+            .unwrap()
+            .0
+            .en_passant(),
+        Some(Square::new(4, 2))
+    );
+    assert_eq!(
+        Board::from_fen("4k3/8/8/8/8/8/8/4K3 w KK").unwrap_err(),
+        FenError::BadCastling("KK".to_string())
+    );
+}
+
+#[test]
+fn castling_rights_round_trip() {
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1";
+    let (mut board, to_move) = Board::from_fen(fen).unwrap();
+
+    assert!(board.castling().allows(Team::White, true));
+    assert!(!board.castling().allows(Team::White, false));
+    assert_eq!(board.to_fen(to_move), fen);
+
+    // moving the rook gives up its side, the king gives up both
+    board.make_move(Move::new(Square::new(7, 0), Square::new(7, 1)));
+    board.make_move(Move::new(Square::new(4, 7), Square::new(4, 6)));
+
+    assert_eq!(
+        board.to_fen(Team::White),
+        "r6r/4k3/8/8/8/8/7R/R3K3 w - - 2 2"
+    );
+}
+
+#[test]
+fn impossible_positions_are_refused() {
+    assert_eq!(
+        Board::from_fen("8/8/8/8/8/8/8/4K3 w - - 0 1").unwrap_err(),
+        FenError::KingCount(Team::Black, 0)
+    );
+    assert_eq!(
+        Board::from_fen("4k3/8/8/8/8/8/8/3KK3 w - - 0 1").unwrap_err(),
+        FenError::KingCount(Team::White, 2)
+    );
+    assert_eq!(
+        Board::from_fen("4k3/8/8/8/8/8/8/P3K3 w - - 0 1").unwrap_err(),
+        FenError::PawnOnBackRank(Square::new(0, 0))
+    );
+    assert_eq!(
+        Board::from_fen("4k2R/8/8/8/8/8/8/4K3 w - - 0 1").unwrap_err(),
+        FenError::OpponentInCheck
+    );
+}
@@ -0,0 +1,42 @@
+use chess_core::*;
+
+#[test]
+fn perft_of_the_starting_position() {
+    let board = Board::starting_position();
+
+    assert_eq!(perft(&board, Team::White, 1), 20);
+    assert_eq!(perft(&board, Team::White, 2), 400);
+    assert_eq!(perft(&board, Team::White, 3), 8_902);
+    assert_eq!(perft(&board, Team::White, 4), 197_281);
+}
+
+// en passant captures that would uncover a check on the king
+#[test]
+fn perft_with_pinned_en_passant_captures() {
+    let (board, to_move) = Board::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap();
+
+    assert_eq!(perft(&board, to_move, 1), 14);
+    assert_eq!(perft(&board, to_move, 2), 191);
+    assert_eq!(perft(&board, to_move, 3), 2_812);
+    assert_eq!(perft(&board, to_move, 4), 43_238);
+}
+
+#[test]
+fn perft_with_promotions_and_checks() {
+    let (board, to_move) = Board::from_fen("n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1").unwrap();
+
+    assert_eq!(perft(&board, to_move, 1), 24);
+    assert_eq!(perft(&board, to_move, 2), 496);
+    assert_eq!(perft(&board, to_move, 3), 9_483);
+}
+
+#[test]
+fn perft_of_kiwipete() {
+    let (board, to_move) =
+        Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            .unwrap();
+
+    assert_eq!(perft(&board, to_move, 1), 48);
+    assert_eq!(perft(&board, to_move, 2), 2_039);
+    assert_eq!(perft(&board, to_move, 3), 97_862);
+}
@@ -0,0 +1,228 @@
+use chess_core::*;
+
+const SCHOLARS_MATE: &str = r#"[Event "Casual game"]
+[White "Anna"]
+[Black "Ben"]
+[Result "1-0"]
+
+1. e4 e5 2. Qh5 {threatening the f7 pawn} Nc6 (2... g6 3. Qf3) 3.Bc4 Nf6?? ; a blunder
+4. Qxf7# 1-0
+"#;
+
+#[test]
+fn pgn_tags_moves_and_result_are_read() {
+    let pgn = Pgn::parse(SCHOLARS_MATE).unwrap();
+
+    assert_eq!(pgn.tag("White"), Some("Anna"));
+    assert_eq!(pgn.tag("Round"), None);
+    assert_eq!(
+        pgn.moves,
+        ["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6??", "Qxf7#"]
+    );
+    assert_eq!(pgn.result.as_deref(), Some("1-0"));
+}
+
+#[test]
+fn pgn_game_is_played_out() {
+    let pgn = Pgn::parse(SCHOLARS_MATE).unwrap();
+    let moves = pgn.play().unwrap();
+    let (mut board, _) = pgn.starting_position().unwrap();
+
+    for mv in &moves {
+        board.make_move(*mv);
+    }
+
+    assert_eq!(moves.len(), 7);
+    assert_eq!(moves[6], Move::new(Square::new(7, 4), Square::new(5, 6)));
+    assert_eq!(
+        outcome(&board, Team::Black),
+        Some(Outcome::Checkmate {
+            winner: Team::White
+        })
+    );
+}
+
+#[test]
+fn pgn_starts_from_its_fen_tag() {
+    let pgn = Pgn::parse("[FEN \"4k3/P7/8/8/8/8/8/4K3 w - - 0 1\"]\n\n1. a8=Q+ Kd7 *").unwrap();
+    let moves = pgn.play().unwrap();
+
+    assert_eq!(moves[0].promotion, Some(PieceKind::Queen));
+    assert_eq!(pgn.result.as_deref(), Some("*"));
+}
+
+#[test]
+fn pgn_with_an_illegal_move_is_refused() {
+    let pgn = Pgn::parse("1. e4 e5 2. e5 *").unwrap();
+
+    assert_eq!(
+        pgn.play().unwrap_err(),
+        PgnError::IllegalMove {
+            ply: 2,
+            san: "e5".to_string()
+        }
+    );
+}
+
+#[test]
+fn san_is_disambiguated_by_file_or_rank() {
+    let (board, to_move) = Board::from_fen("4k3/8/8/8/8/8/1N3N2/N3K3 w - - 0 1").unwrap();
+
+    assert_eq!(parse_san(&board, to_move, "Nd3"), None);
+    assert_eq!(
+        parse_san(&board, to_move, "Nbd3"),
+        Some(Move::new(Square::new(1, 1), Square::new(3, 2)))
+    );
+    assert_eq!(
+        parse_san(&board, to_move, "N1b3"),
+        Some(Move::new(Square::new(0, 0), Square::new(1, 2)))
+    );
+    assert_eq!(
+        parse_san(&board, to_move, "Nb2d3"),
+        Some(Move::new(Square::new(1, 1), Square::new(3, 2)))
+    );
+}
+
+#[test]
+fn san_pawn_captures_and_promotions_are_read() {
+    let (board, to_move) = Board::from_fen("1n2k3/P7/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+
+    assert_eq!(
+        parse_san(&board, to_move, "exd6"),
+        Some(Move::new(Square::new(4, 4), Square::new(3, 5)))
+    );
+    assert_eq!(
+        parse_san(&board, to_move, "axb8=N"),
+        Some(Move {
+            promotion: Some(PieceKind::Knight),
+            ..Move::new(Square::new(0, 6), Square::new(1, 7))
+        })
+    );
+    assert_eq!(
+        parse_san(&board, to_move, "a8Q"),
+        Some(Move {
+            promotion: Some(PieceKind::Queen),
+            ..Move::new(Square::new(0, 6), Square::new(0, 7))
+        })
+    );
+    // a pawn reaching the last rank has to be promoted
+    assert_eq!(parse_san(&board, to_move, "a8"), None);
+    assert_eq!(parse_san(&board, to_move, "O-O"), None);
+}
+
+#[test]
+fn san_is_written_with_captures_checks_and_disambiguation() {
+    let (board, _) = Board::from_fen("4k3/8/8/N7/8/8/1N3N2/N3K3 w - - 0 1").unwrap();
+
+    assert_eq!(
+        to_san(&board, Move::new(Square::new(1, 1), Square::new(3, 2))),
+        "Nbd3"
+    );
+    assert_eq!(
+        to_san(&board, Move::new(Square::new(0, 0), Square::new(1, 2))),
+        "N1b3"
+    );
+    assert_eq!(
+        to_san(&board, Move::new(Square::new(5, 1), Square::new(6, 3))),
+        "Ng4"
+    );
+
+    let (board, _) = Board::from_fen("1n2k3/P7/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+
+    assert_eq!(
+        to_san(&board, Move::new(Square::new(4, 4), Square::new(3, 5))),
+        "exd6"
+    );
+    assert_eq!(
+        to_san(
+            &board,
+            Move {
+                promotion: Some(PieceKind::Queen),
+                ..Move::new(Square::new(0, 6), Square::new(1, 7))
+            }
+        ),
+        "axb8=Q+"
+    );
+
+    let (board, _) = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+
+    assert_eq!(
+        to_san(&board, Move::new(Square::new(4, 7), Square::new(6, 7))),
+        "O-O"
+    );
+    assert_eq!(
+        to_san(&board, Move::new(Square::new(4, 7), Square::new(2, 7))),
+        "O-O-O"
+    );
+}
+
+#[test]
+fn san_round_trips_through_a_game() {
+    let pgn = Pgn::parse(SCHOLARS_MATE).unwrap();
+    let (mut board, _) = pgn.starting_position().unwrap();
+    let mut written = Vec::new();
+
+    for mv in pgn.play().unwrap() {
+        written.push(to_san(&board, mv));
+        board.make_move(mv);
+    }
+
+    assert_eq!(written, ["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"]);
+}
+
+#[test]
+fn pgn_is_written_and_read_back() {
+    let pgn = Pgn {
+        tags: vec![
+            ("White".to_string(), "Anna \"the rook\"".to_string()),
+            (
+                "FEN".to_string(),
+                "4k3/P7/8/8/8/8/8/4K3 b - - 0 12".to_string(),
+            ),
+        ],
+        moves: vec!["Kd7".to_string(), "a8=Q".to_string()],
+        result: Some(
+            Outcome::Resignation {
+                winner: Team::White,
+            }
+            .result()
+            .to_string(),
+        ),
+        comment: Some("Black resigns".to_string()),
+    };
+    let text = pgn.to_string();
+
+    assert!(text.ends_with("\n12... Kd7 13. a8=Q {Black resigns} 1-0\n"));
+
+    let read = Pgn::parse(&text).unwrap();
+    assert_eq!(read.tags, pgn.tags);
+    assert_eq!(read.moves, pgn.moves);
+    assert_eq!(read.result, pgn.result);
+    assert_eq!(read.comment, None);
+}
+
+#[test]
+fn pgn_game_with_castling_is_played_out() {
+    let pgn = Pgn::parse("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. O-O Nf6 5. d3 0-0 *").unwrap();
+    let (mut board, _) = pgn.starting_position().unwrap();
+    let mut written = Vec::new();
+
+    for mv in pgn.play().unwrap() {
+        written.push(to_san(&board, mv));
+        board.make_move(mv);
+    }
+
+    assert_eq!(written[6], "O-O");
+    assert_eq!(written[9], "O-O");
+    assert_eq!(
+        board.to_fen(Team::White),
+        "r1bq1rk1/pppp1ppp/2n2n2/2b1p3/2B1P3/3P1N2/PPP2PPP/RNBQ1RK1 w - - 1 6"
+    );
+}
+
+#[test]
+fn unbalanced_variation_end_is_skipped() {
+    let pgn = Pgn::parse("1. e4 ) e5 (2. d4) 2. Nf3 *").unwrap();
+
+    assert_eq!(pgn.moves, ["e4", "e5", "Nf3"]);
+}
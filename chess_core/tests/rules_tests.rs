@@ -0,0 +1,452 @@
+use chess_core::*;
+
+#[test]
+fn starting_position_has_twenty_moves() {
+    let board = Board::starting_position();
+    let moves: usize = board
+        .pieces()
+        .filter(|(_, piece)| piece.team == Team::White)
+        .map(|(square, _)| legal_moves(&board, square).len())
+        .sum();
+
+    assert_eq!(moves, 20);
+}
+
+#[test]
+fn pawn_can_step_once_or_twice_from_its_rank() {
+    let board = Board::starting_position();
+    let from = Square::new(4, 1);
+
+    assert!(is_legal(&board, Move::new(from, Square::new(4, 2))));
+    assert!(is_legal(&board, Move::new(from, Square::new(4, 3))));
+    assert!(!is_legal(&board, Move::new(from, Square::new(4, 4))));
+}
+
+#[test]
+fn making_a_move_returns_the_captured_piece() {
+    let mut board = Board::default();
+    board.set_piece(
+        Square::new(0, 0),
+        Some(Piece::new(PieceKind::Rook, Team::White)),
+    );
+    board.set_piece(
+        Square::new(0, 7),
+        Some(Piece::new(PieceKind::Queen, Team::Black)),
+    );
+
+    let captured = board.make_move(Move::new(Square::new(0, 0), Square::new(0, 7)));
+
+    assert_eq!(captured, Some(Piece::new(PieceKind::Queen, Team::Black)));
+    assert_eq!(board.piece_at(Square::new(0, 0)), None);
+}
+
+#[test]
+fn king_is_in_check_from_a_sliding_piece_until_blocked() {
+    let mut board = Board::default();
+    board.set_piece(
+        Square::new(4, 0),
+        Some(Piece::new(PieceKind::King, Team::White)),
+    );
+    board.set_piece(
+        Square::new(4, 7),
+        Some(Piece::new(PieceKind::Rook, Team::Black)),
+    );
+
+    assert!(in_check(&board, Team::White));
+    assert!(!in_check(&board, Team::Black));
+
+    board.set_piece(
+        Square::new(4, 3),
+        Some(Piece::new(PieceKind::Knight, Team::White)),
+    );
+    assert!(!in_check(&board, Team::White));
+}
+
+#[test]
+fn pawns_attack_diagonally_forward() {
+    let mut board = Board::default();
+    board.set_piece(
+        Square::new(3, 3),
+        Some(Piece::new(PieceKind::Pawn, Team::White)),
+    );
+
+    assert!(is_attacked(&board, Square::new(2, 4), Team::White));
+    assert!(is_attacked(&board, Square::new(4, 4), Team::White));
+    assert!(!is_attacked(&board, Square::new(3, 4), Team::White));
+    assert!(!is_attacked(&board, Square::new(2, 2), Team::White));
+}
+
+#[test]
+fn back_rank_mate_ends_the_game() {
+    let mut board = Board::default();
+    board.set_piece(
+        Square::new(6, 0),
+        Some(Piece::new(PieceKind::King, Team::White)),
+    );
+    for x in 5..8 {
+        board.set_piece(
+            Square::new(x, 1),
+            Some(Piece::new(PieceKind::Pawn, Team::White)),
+        );
+    }
+    board.set_piece(
+        Square::new(0, 0),
+        Some(Piece::new(PieceKind::Rook, Team::Black)),
+    );
+
+    assert_eq!(
+        outcome(&board, Team::White),
+        Some(Outcome::Checkmate {
+            winner: Team::Black
+        })
+    );
+    assert_eq!(outcome(&Board::starting_position(), Team::White), None);
+}
+
+#[test]
+fn no_move_without_check_is_stalemate() {
+    let mut board = Board::default();
+    board.set_piece(
+        Square::new(0, 7),
+        Some(Piece::new(PieceKind::King, Team::Black)),
+    );
+    board.set_piece(
+        Square::new(2, 6),
+        Some(Piece::new(PieceKind::Queen, Team::White)),
+    );
+    board.set_piece(
+        Square::new(2, 5),
+        Some(Piece::new(PieceKind::King, Team::White)),
+    );
+
+    assert_eq!(outcome(&board, Team::Black), Some(Outcome::Stalemate));
+}
+
+#[test]
+fn pawn_that_advanced_two_squares_can_be_taken_en_passant() {
+    let mut board = Board::starting_position();
+    board.make_move(Move::new(Square::new(4, 1), Square::new(4, 4)));
+    board.make_move(Move::new(Square::new(3, 6), Square::new(3, 4)));
+
+    let capture = Move::new(Square::new(4, 4), Square::new(3, 5));
+    assert!(legal_moves(&board, Square::new(4, 4)).contains(&capture));
+    assert_eq!(
+        board.make_move(capture),
+        Some(Piece::new(PieceKind::Pawn, Team::Black))
+    );
+    assert_eq!(board.piece_at(Square::new(3, 4)), None);
+    assert_eq!(board.en_passant(), None);
+}
+
+#[test]
+fn pawn_reaching_the_last_rank_is_promoted() {
+    let mut board = Board::default();
+    board.set_piece(
+        Square::new(0, 6),
+        Some(Piece::new(PieceKind::Pawn, Team::White)),
+    );
+    let mv = Move::new(Square::new(0, 6), Square::new(0, 7));
+
+    // the pawn can't stay a pawn
+    assert!(is_promotion(&board, mv));
+    assert!(!is_legal(&board, mv));
+    assert_eq!(legal_moves(&board, mv.from).len(), PROMOTIONS.len());
+
+    board.make_move(Move {
+        promotion: Some(PieceKind::Knight),
+        ..mv
+    });
+    assert_eq!(
+        board.piece_at(mv.to),
+        Some(Piece::new(PieceKind::Knight, Team::White))
+    );
+}
+
+#[test]
+fn moves_leaving_the_king_in_check_are_not_legal() {
+    let mut board = Board::default();
+    board.set_piece(
+        Square::new(4, 0),
+        Some(Piece::new(PieceKind::King, Team::White)),
+    );
+    board.set_piece(
+        Square::new(4, 1),
+        Some(Piece::new(PieceKind::Knight, Team::White)),
+    );
+    board.set_piece(
+        Square::new(4, 7),
+        Some(Piece::new(PieceKind::Rook, Team::Black)),
+    );
+    board.set_piece(
+        Square::new(3, 7),
+        Some(Piece::new(PieceKind::Rook, Team::Black)),
+    );
+
+    // the knight is pinned against its king
+    assert!(legal_moves(&board, Square::new(4, 1)).is_empty());
+    // and the king can't step onto the file of the other rook
+    let king_moves = legal_moves(&board, Square::new(4, 0));
+    assert_eq!(king_moves.len(), 2);
+    assert!(king_moves.contains(&Move::new(Square::new(4, 0), Square::new(5, 0))));
+    assert!(king_moves.contains(&Move::new(Square::new(4, 0), Square::new(5, 1))));
+}
+
+#[test]
+fn pinned_bishop_only_slides_along_the_pin() {
+    let mut board = Board::default();
+    board.set_piece(
+        Square::new(0, 0),
+        Some(Piece::new(PieceKind::King, Team::White)),
+    );
+    board.set_piece(
+        Square::new(2, 2),
+        Some(Piece::new(PieceKind::Bishop, Team::White)),
+    );
+    board.set_piece(
+        Square::new(4, 4),
+        Some(Piece::new(PieceKind::Queen, Team::Black)),
+    );
+
+    let ray = vec![Square::new(1, 1), Square::new(3, 3), Square::new(4, 4)];
+    assert_eq!(pin_ray(&board, Square::new(2, 2)), Some(ray.clone()));
+
+    let mut targets: Vec<Square> = legal_moves(&board, Square::new(2, 2))
+        .into_iter()
+        .map(|mv| mv.to)
+        .collect();
+    targets.sort_by_key(|square| (square.x, square.y));
+    assert_eq!(targets, ray);
+}
+
+#[test]
+fn fifty_moves_without_capture_or_pawn_move_draw_the_game() {
+    let mut board = Board::default();
+    board.set_piece(
+        Square::new(0, 0),
+        Some(Piece::new(PieceKind::King, Team::White)),
+    );
+    board.set_piece(
+        Square::new(7, 7),
+        Some(Piece::new(PieceKind::King, Team::Black)),
+    );
+    board.set_piece(
+        Square::new(0, 6),
+        Some(Piece::new(PieceKind::Pawn, Team::Black)),
+    );
+    board.set_piece(
+        Square::new(3, 3),
+        Some(Piece::new(PieceKind::Rook, Team::White)),
+    );
+
+    // the rook goes back and forth while the black king does the same
+    let (white, black) = (
+        [Square::new(3, 3), Square::new(3, 4)],
+        [Square::new(7, 7), Square::new(6, 7)],
+    );
+    for i in 0..50 {
+        let (from, to) = (i % 2, (i + 1) % 2);
+        assert_eq!(outcome(&board, Team::White), None);
+        board.make_move(Move::new(white[from], white[to]));
+        board.make_move(Move::new(black[from], black[to]));
+    }
+
+    assert_eq!(board.halfmove_clock(), 100);
+    assert_eq!(outcome(&board, Team::White), Some(Outcome::FiftyMoveRule));
+
+    // a pawn move starts the count again
+    board.make_move(Move::new(Square::new(3, 4), Square::new(3, 3)));
+    board.make_move(Move::new(Square::new(0, 6), Square::new(0, 5)));
+    assert_eq!(board.halfmove_clock(), 0);
+}
+
+#[test]
+fn lone_minor_piece_cannot_mate() {
+    let mut board = Board::default();
+    board.set_piece(
+        Square::new(0, 0),
+        Some(Piece::new(PieceKind::King, Team::White)),
+    );
+    board.set_piece(
+        Square::new(7, 7),
+        Some(Piece::new(PieceKind::King, Team::Black)),
+    );
+    assert!(insufficient_material(&board));
+
+    board.set_piece(
+        Square::new(2, 0),
+        Some(Piece::new(PieceKind::Bishop, Team::White)),
+    );
+    assert!(insufficient_material(&board));
+    assert_eq!(
+        outcome(&board, Team::White),
+        Some(Outcome::InsufficientMaterial)
+    );
+
+    // bishops on squares of both colors can still mate
+    board.set_piece(
+        Square::new(4, 7),
+        Some(Piece::new(PieceKind::Bishop, Team::Black)),
+    );
+    assert!(!insufficient_material(&board));
+}
+
+#[test]
+fn pawn_cannot_jump_over_a_piece_or_from_another_rank() {
+    let mut board = Board::starting_position();
+    board.set_piece(
+        Square::new(4, 2),
+        Some(Piece::new(PieceKind::Knight, Team::Black)),
+    );
+    assert!(!is_legal(
+        &board,
+        Move::new(Square::new(4, 1), Square::new(4, 3))
+    ));
+
+    // a pawn that already moved only steps once
+    let mut board = Board::starting_position();
+    board.make_move(Move::new(Square::new(3, 1), Square::new(3, 2)));
+    assert!(!is_legal(
+        &board,
+        Move::new(Square::new(3, 2), Square::new(3, 4))
+    ));
+}
+
+#[test]
+fn pawn_can_be_underpromoted_to_a_knight() {
+    let mut board = Board::default();
+    board.set_piece(
+        Square::new(0, 0),
+        Some(Piece::new(PieceKind::King, Team::White)),
+    );
+    board.set_piece(
+        Square::new(6, 6),
+        Some(Piece::new(PieceKind::Pawn, Team::White)),
+    );
+    board.set_piece(
+        Square::new(7, 5),
+        Some(Piece::new(PieceKind::King, Team::Black)),
+    );
+    let promotion = |kind| Move {
+        promotion: Some(kind),
+        ..Move::new(Square::new(6, 6), Square::new(6, 7))
+    };
+
+    // only the knight checks from the last rank
+    let mut queen = board.clone();
+    queen.make_move(promotion(PieceKind::Queen));
+    assert!(!in_check(&queen, Team::Black));
+
+    assert!(is_legal(&board, promotion(PieceKind::Knight)));
+    board.make_move(promotion(PieceKind::Knight));
+    assert!(in_check(&board, Team::Black));
+}
+
+#[test]
+fn kings_never_stand_next_to_each_other() {
+    let mut board = Board::default();
+    board.set_piece(
+        Square::new(4, 3),
+        Some(Piece::new(PieceKind::King, Team::White)),
+    );
+    board.set_piece(
+        Square::new(4, 5),
+        Some(Piece::new(PieceKind::King, Team::Black)),
+    );
+
+    // the rank between the kings is attacked by both
+    for x in 3..=5 {
+        assert!(!is_legal(
+            &board,
+            Move::new(Square::new(4, 3), Square::new(x, 4))
+        ));
+    }
+    assert_eq!(legal_moves(&board, Square::new(4, 3)).len(), 5);
+}
+
+#[test]
+fn bitboards_follow_the_pieces() {
+    let mut board = Board::starting_position();
+    assert_eq!(board.bitboards().occupied(), 0xFFFF_0000_0000_FFFF);
+    assert_eq!(board.bitboards().team(Team::White), 0xFFFF);
+
+    board.make_move(Move::new(Square::new(4, 1), Square::new(4, 3)));
+    let pawns = board.bitboards().pieces(PieceKind::Pawn, Team::White);
+    assert_eq!(pawns & (1 << 12), 0);
+    assert_ne!(pawns & (1 << 28), 0);
+}
+
+#[test]
+fn undoing_moves_gives_back_the_position() {
+    let (mut board, _) = Board::from_fen("1n2k3/P7/8/3pP3/8/8/8/4K3 w - d6 3 40").unwrap();
+    let before = board.to_fen(Team::White);
+    let moves = [
+        Move::new(Square::new(4, 4), Square::new(3, 5)),
+        Move::new(Square::new(1, 7), Square::new(2, 5)),
+        Move {
+            promotion: Some(PieceKind::Queen),
+            ..Move::new(Square::new(0, 6), Square::new(0, 7))
+        },
+    ];
+    let mut undos = Vec::new();
+
+    for mv in moves {
+        undos.push(board.undo_for(mv).unwrap());
+        board.make_move(mv);
+    }
+    while let Some(undo) = undos.pop() {
+        board.undo_move(undo);
+    }
+
+    assert_eq!(board.to_fen(Team::White), before);
+    assert_eq!(board.pieces().count(), 6);
+}
+
+#[test]
+fn king_castles_through_empty_and_unattacked_squares() {
+    let (board, _) = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let king = Square::new(4, 0);
+
+    assert!(is_legal(&board, Move::new(king, Square::new(6, 0))));
+    assert!(is_legal(&board, Move::new(king, Square::new(2, 0))));
+
+    // the rook on f8 attacks the square the king passes through, the knight blocks the other side
+    let (board, _) = Board::from_fen("r3kr2/8/8/8/8/8/8/RN2K2R w KQq - 0 1").unwrap();
+
+    assert!(!is_legal(&board, Move::new(king, Square::new(6, 0))));
+    assert!(!is_legal(&board, Move::new(king, Square::new(2, 0))));
+
+    // in check, the king can't castle at all
+    let (board, _) = Board::from_fen("r3k2r/8/8/8/8/8/4q3/R3K2R w KQkq - 0 1").unwrap();
+
+    assert!(!is_legal(&board, Move::new(king, Square::new(6, 0))));
+}
+
+#[test]
+fn castling_moves_the_rook_over_the_king() {
+    let (mut board, _) = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+
+    board.make_move(Move::new(Square::new(4, 7), Square::new(2, 7)));
+
+    assert_eq!(
+        board.piece_at(Square::new(3, 7)),
+        Some(Piece::new(PieceKind::Rook, Team::Black))
+    );
+    assert_eq!(board.piece_at(Square::new(0, 7)), None);
+    assert_eq!(
+        board.to_fen(Team::White),
+        "2kr3r/8/8/8/8/8/8/R3K2R w KQ - 1 2"
+    );
+}
+
+#[test]
+fn undoing_a_castle_gives_back_the_rook_and_the_rights() {
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    let (mut board, _) = Board::from_fen(fen).unwrap();
+    let mv = Move::new(Square::new(4, 0), Square::new(6, 0));
+
+    let undo = board.undo_for(mv).unwrap();
+    board.make_move(mv);
+    board.undo_move(undo);
+
+    assert_eq!(board.to_fen(Team::White), fen);
+}
@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use chess_core::*;
+
+#[test]
+fn starting_position_is_even() {
+    let board = Board::starting_position();
+
+    assert_eq!(evaluate(&board, Team::White), 0);
+    assert_eq!(evaluate(&board, Team::Black), 0);
+}
+
+#[test]
+fn evaluation_is_the_other_team_score_flipped() {
+    let (board, _) = Board::from_fen("4k3/8/8/8/3Q4/8/8/4K3 w - - 0 1").unwrap();
+
+    assert!(evaluate(&board, Team::White) > piece_value(PieceKind::Queen) / 2);
+    assert_eq!(
+        evaluate(&board, Team::White),
+        -evaluate(&board, Team::Black)
+    );
+}
+
+#[test]
+fn takes_a_hanging_queen() {
+    let (board, to_move) = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+
+    assert_eq!(best_move(&board, to_move, 2), Move::from_uci("d2d5"));
+}
+
+#[test]
+fn finds_the_mate_in_one() {
+    let (board, to_move) = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+
+    assert_eq!(best_move(&board, to_move, 2), Move::from_uci("a1a8"));
+}
+
+#[test]
+fn mated_team_has_no_move() {
+    let (board, to_move) = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+
+    assert_eq!(best_move(&board, to_move, 2), None);
+    assert_eq!(minimax(&board, to_move, 2, 0), -MATE);
+}
+
+#[test]
+fn leaves_a_defended_pawn_alone() {
+    let (board, to_move) = Board::from_fen("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1").unwrap();
+
+    assert_ne!(best_move(&board, to_move, 1), Move::from_uci("d1d5"));
+}
+
+#[test]
+fn searching_within_a_budget_finds_a_legal_move() {
+    let board = Board::starting_position();
+    let mv = best_move_within(&board, Team::White, Duration::from_millis(50)).unwrap();
+
+    assert!(is_legal(&board, mv));
+}
+
+#[test]
+fn searching_within_a_budget_finds_the_mate_in_one() {
+    let (board, to_move) = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+
+    assert_eq!(
+        best_move_within(&board, to_move, Duration::from_millis(200)),
+        Move::from_uci("a1a8")
+    );
+}
@@ -0,0 +1,22 @@
+use chess_core::*;
+
+#[test]
+fn uci_moves_round_trip() {
+    let mv = Move::new(Square::new(4, 1), Square::new(4, 3));
+    let promotion = Move {
+        promotion: Some(PieceKind::Knight),
+        ..Move::new(Square::new(4, 6), Square::new(4, 7))
+    };
+
+    assert_eq!(mv.to_uci(), "e2e4");
+    assert_eq!(Move::from_uci("e2e4"), Some(mv));
+    assert_eq!(promotion.to_uci(), "e7e8n");
+    assert_eq!(Move::from_uci("e7e8n"), Some(promotion));
+}
+
+#[test]
+fn malformed_uci_moves_are_refused() {
+    for uci in ["", "e2", "e2e", "e2e9", "i2e4", "e7e8k", "e7e8qq", "e2é4"] {
+        assert_eq!(Move::from_uci(uci), None, "{}", uci);
+    }
+}
@@ -0,0 +1,147 @@
+use bevy::{prelude::*, render::camera::Viewport};
+use bevy_mod_picking::PickingCamera;
+use iyes_loopless::prelude::*;
+
+use crate::{board::TILE_SIZE, hotkeys_enabled, piece::PieceType, settings::Settings};
+
+// side of the lens in logical pixels
+const LENS_SIZE: f32 = 240.0;
+// how much bigger the board looks through the lens
+const LENS_ZOOM: f32 = 2.5;
+// how much bigger the pieces are drawn in low vision mode, they overflow their tiles a bit
+const LARGE_PIECE_SCALE: f32 = 1.25;
+
+// for players who have trouble telling the pieces apart, both picked in the settings: a low vision
+// mode drawing the pieces bigger and the squares they can move to more boldly, and a lens that
+// follows the cursor showing the board under it enlarged, also turned on and off with M
+pub struct AccessibilityPlugin;
+
+// the camera drawing the lens, over the one drawing the board
+#[derive(Component)]
+pub struct Magnifier;
+
+// the camera the board is looked at and picked from
+type BoardCameraQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Camera, &'static GlobalTransform),
+    (With<PickingCamera>, Without<Magnifier>),
+>;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(Self::toggle_magnifier.run_if(hotkeys_enabled))
+            .add_system(Self::show_magnifier)
+            .add_system(Self::follow_cursor)
+            .add_system(Self::resize_pieces);
+    }
+}
+
+impl AccessibilityPlugin {
+    fn toggle_magnifier(keys: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+        if keys.just_pressed(KeyCode::M) {
+            settings.magnifier = !settings.magnifier;
+        }
+    }
+
+    // spawns the lens once it's turned on, from the settings or with M, and despawns it once it's
+    // turned off
+    fn show_magnifier(
+        mut commands: Commands,
+        settings: Res<Settings>,
+        magnifier_q: Query<Entity, With<Magnifier>>,
+    ) {
+        if !settings.is_changed() {
+            return;
+        }
+
+        match (settings.magnifier, magnifier_q.get_single()) {
+            (false, Ok(magnifier)) => commands.entity(magnifier).despawn_recursive(),
+            (true, Err(_)) => Self::spawn_magnifier(&mut commands),
+            _ => {}
+        }
+    }
+
+    fn spawn_magnifier(commands: &mut Commands) {
+        let mut camera = Camera2dBundle::default();
+        camera.camera.priority = 1;
+        // hidden until it's known where the cursor is
+        camera.camera.is_active = false;
+        camera.projection.scale = 1.0 / LENS_ZOOM;
+
+        commands.spawn((camera, Magnifier));
+    }
+
+    // draws the pieces bigger in low vision mode, the ones spawned again too
+    fn resize_pieces(
+        settings: Res<Settings>,
+        mut piece_q: Query<&mut Sprite, With<PieceType>>,
+        added_q: Query<(), Added<PieceType>>,
+    ) {
+        if !settings.is_changed() && added_q.is_empty() {
+            return;
+        }
+
+        let size = match settings.low_vision {
+            true => TILE_SIZE * LARGE_PIECE_SCALE,
+            false => TILE_SIZE,
+        };
+        for mut sprite in piece_q.iter_mut() {
+            sprite.custom_size = Some(Vec2::splat(size));
+        }
+    }
+
+    // centers the lens on the cursor, looking at the point of the board under it
+    fn follow_cursor(
+        windows: Res<Windows>,
+        board_camera_q: BoardCameraQuery,
+        mut magnifier_q: Query<(&mut Camera, &mut Transform), With<Magnifier>>,
+    ) {
+        let (mut lens, mut lens_t) = match magnifier_q.get_single_mut() {
+            Ok(magnifier) => magnifier,
+            Err(_) => return,
+        };
+        let (window, (board_camera, board_camera_gt)) =
+            match (windows.get_primary(), board_camera_q.get_single()) {
+                (Some(window), Ok(board_camera)) => (window, board_camera),
+                _ => return,
+            };
+        let ray = window.cursor_position().and_then(|cursor| {
+            Some((
+                cursor,
+                board_camera.viewport_to_world(board_camera_gt, cursor)?,
+            ))
+        });
+        let (cursor, ray) = match ray {
+            Some(cursor_ray) => cursor_ray,
+            None => {
+                lens.is_active = false;
+                return;
+            }
+        };
+
+        let board_camera_t = board_camera_gt.compute_transform();
+        lens_t.translation = ray.origin.truncate().extend(board_camera_t.translation.z);
+        lens_t.rotation = board_camera_t.rotation;
+
+        // the viewport is placed in physical pixels from the top left corner, the cursor is given
+        // in logical pixels from the bottom left one
+        let scale = window.scale_factor() as f32;
+        let (width, height) = (window.physical_width(), window.physical_height());
+        let size = ((LENS_SIZE * scale) as u32).min(width).min(height);
+        let (x, y) = (
+            (cursor.x * scale) as u32,
+            height.saturating_sub((cursor.y * scale) as u32),
+        );
+
+        lens.viewport = Some(Viewport {
+            physical_position: UVec2::new(
+                x.saturating_sub(size / 2).min(width - size),
+                y.saturating_sub(size / 2).min(height - size),
+            ),
+            physical_size: UVec2::splat(size),
+            ..default()
+        });
+        lens.is_active = true;
+    }
+}
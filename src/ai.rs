@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+    window::RequestRedraw,
+};
+use bevy_mod_picking::PickingPluginsState;
+use chess_core::{best_move_within, evaluate, Move, Outcome, Team};
+use futures_lite::future;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    board::ChessBoard,
+    game::{end_game, DrawOffer, GameState},
+    history::MoveHistory,
+    hot_seat::HotSeat,
+    movement::MoveRequested,
+    piece::{PieceSystem, TurnState},
+    settings::Settings,
+};
+
+// how long the computer thinks about every move, looking a move further ahead each time until
+// it's up
+const THINKING_TIME: Duration = Duration::from_secs(2);
+// the team the computer plays, when the game is played against it
+pub const COMPUTER_TEAM: Team = Team::Black;
+
+// the computer's move being searched for away from the frame, so the app doesn't freeze, and the
+// position it was asked for
+#[derive(Resource)]
+pub struct Thinking {
+    position: String,
+    task: Task<Option<Move>>,
+}
+
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            Self::start_thinking
+                .run_in_state(GameState::Playing)
+                .run_unless_resource_exists::<Thinking>(),
+        )
+        .add_system(
+            Self::play_move
+                .run_in_state(GameState::Playing)
+                .run_if_resource_exists::<Thinking>()
+                .before(PieceSystem::MovePiece),
+        )
+        .add_system(Self::keep_updating.run_if_resource_exists::<Thinking>())
+        .add_system(Self::answer_draw_offer.run_in_state(GameState::Playing));
+    }
+}
+
+impl AiPlugin {
+    // the board can't be picked while the computer thinks, so its pieces are only moved by it
+    fn start_thinking(
+        mut commands: Commands,
+        settings: Res<Settings>,
+        board: Res<ChessBoard>,
+        turn: Res<TurnState>,
+        history: Res<MoveHistory>,
+        mut picking: ResMut<PickingPluginsState>,
+    ) {
+        if !settings.vs_computer || turn.0 != COMPUTER_TEAM {
+            return;
+        }
+        // once moves are taken back, the computer waits for them to be made again, or for the
+        // player's move to be taken back too, instead of playing again right away
+        if !history.redos.is_empty() {
+            return;
+        }
+
+        let (position, to_move) = (board.0.clone(), turn.0);
+        let task = AsyncComputeTaskPool::get()
+            .spawn(async move { best_move_within(&position, to_move, THINKING_TIME) });
+
+        picking.enable_picking = false;
+        commands.insert_resource(Thinking {
+            position: board.0.to_fen(turn.0),
+            task,
+        });
+    }
+
+    // in power saving mode the app only updates on input, so it asks for another frame while the
+    // computer thinks, or the move found would wait for the player to move the mouse
+    fn keep_updating(mut redraw: EventWriter<RequestRedraw>) {
+        redraw.send(RequestRedraw);
+    }
+
+    // takes the draw offered by the player unless the computer is better off, as far as it can
+    // tell from the position alone
+    fn answer_draw_offer(
+        mut commands: Commands,
+        settings: Res<Settings>,
+        board: Res<ChessBoard>,
+        mut offer: ResMut<DrawOffer>,
+    ) {
+        if !settings.vs_computer || offer.0 != Some(COMPUTER_TEAM.opposite()) {
+            return;
+        }
+
+        match evaluate(&board.0, COMPUTER_TEAM) <= 0 {
+            true => end_game(&mut commands, &mut offer, Outcome::DrawAgreed),
+            false => {
+                info!("The computer declines the draw");
+                offer.0 = None;
+            }
+        }
+    }
+
+    // the move found is asked for as the player's are, unless the game went on without it, it
+    // was taken back or another game was started meanwhile
+    fn play_move(
+        mut commands: Commands,
+        mut thinking: ResMut<Thinking>,
+        settings: Res<Settings>,
+        board: Res<ChessBoard>,
+        hot_seat: Res<HotSeat>,
+        mut picking: ResMut<PickingPluginsState>,
+        mut move_request: EventWriter<MoveRequested>,
+    ) {
+        let found = match future::block_on(future::poll_once(&mut thinking.task)) {
+            Some(found) => found,
+            None => return,
+        };
+
+        commands.remove_resource::<Thinking>();
+        picking.enable_picking = !hot_seat.hidden();
+        if !settings.vs_computer || board.0.to_fen(COMPUTER_TEAM) != thinking.position {
+            return;
+        }
+
+        if let Some(mv) = found {
+            info!(mv = %mv.to_uci(), "computer move");
+            move_request.send(MoveRequested(mv));
+        }
+    }
+}
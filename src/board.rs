@@ -1,4 +1,4 @@
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_ecs_tilemap::{
     prelude::{
         get_tilemap_center_transform, TilemapGridSize, TilemapId, TilemapSize, TilemapTexture,
@@ -7,12 +7,66 @@ use bevy_ecs_tilemap::{
     tiles::{TileBundle, TileColor, TilePos, TileStorage},
     TilemapBundle,
 };
-
-use crate::{bishop, king, knight, pawn, piece::Team, queen, rock, GameAssets};
+use chess_core::{Board, PieceKind, Square, STARTING_FEN};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    bishop, king, knight, pawn,
+    piece::{PieceType, TurnState},
+    playback::ReviewPosition,
+    queen, rook, GameAssets,
+};
 
 pub const TILE_SIZE: f32 = 64.0;
 
-#[derive(Debug)]
+// the position the game is played on, the tiles and the piece entities of the main board only
+// show it
+#[derive(Resource)]
+pub struct ChessBoard(pub Board);
+
+// the FEN the game starts from, insert it before adding the game to start from another position
+#[derive(Resource, Clone, Debug)]
+pub struct StartingPosition(pub String);
+
+impl Default for StartingPosition {
+    fn default() -> Self {
+        Self(STARTING_FEN.to_string())
+    }
+}
+
+// sent when the position is replaced by one that wasn't reached by a move, like a game loaded
+// from a file, so the pieces shown are spawned again from the board
+pub struct PositionReset;
+
+// tells apart the boards in the world, the tilemap of every board and the tiles, pieces and
+// highlights on it carry the same id
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BoardId(pub u32);
+
+impl BoardId {
+    // the board the game is played on
+    pub const MAIN: Self = Self(0);
+}
+
+// the rules live in their own crate, which knows nothing of tiles, so squares and tiles are
+// converted by hand. both count files and ranks from white's bottom left corner
+pub fn to_square(tile_pos: TilePos) -> Square {
+    Square::new(tile_pos.x, tile_pos.y)
+}
+
+pub fn to_tile_pos(square: Square) -> TilePos {
+    TilePos {
+        x: square.x,
+        y: square.y,
+    }
+}
+
+// wether the tile is drawn with the light color, the bottom left one being dark
+pub fn is_light_tile(tile_pos: &TilePos) -> bool {
+    !(tile_pos.x + tile_pos.y).is_multiple_of(2)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Tile {
     Empty,
     NotEmpty,
@@ -25,12 +79,71 @@ pub struct TileState {
     pub piece_ent: Option<Entity>,
 }
 
+// every board's tilemap, with what's needed to go from a tile to the world and back
+pub type TilemapQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static BoardId,
+        &'static TileStorage,
+        &'static TilemapGridSize,
+        &'static TilemapSize,
+        &'static TilemapType,
+    ),
+>;
+
+// the state of every tile, and the tilemaps they are in
+#[derive(SystemParam)]
+pub struct BoardTiles<'w, 's> {
+    pub tile_state_q: Query<'w, 's, &'static mut TileState>,
+    pub tilemap_q: TilemapQuery<'w, 's>,
+}
+
+// what the pieces are spawned with: their images and the quad under them they are picked by
+#[derive(SystemParam)]
+pub struct PieceSpawner<'w, 's> {
+    pub commands: Commands<'w, 's>,
+    pub game_assets: Res<'w, GameAssets>,
+    pub meshes: ResMut<'w, Assets<Mesh>>,
+    pub material: ResMut<'w, Assets<ColorMaterial>>,
+}
+
+// the tilemap of the board with the given id, if it has been spawned
+pub fn board_tilemap<'a>(
+    tilemap_q: &'a TilemapQuery,
+    board_id: BoardId,
+) -> Option<(
+    &'a TileStorage,
+    &'a TilemapGridSize,
+    &'a TilemapSize,
+    &'a TilemapType,
+)> {
+    tilemap_q
+        .iter()
+        .find(|(id, ..)| **id == board_id)
+        .map(|(_, storage, grid_size, map_size, map_type)| (storage, grid_size, map_size, map_type))
+}
+
 pub struct BoardPlugin;
 
 impl Plugin for BoardPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(Self::tilemap_builder)
-            .add_startup_system_to_stage(StartupStage::PostStartup, Self::setup_pieces);
+        let fen = app
+            .world
+            .get_resource_or_insert_with(StartingPosition::default)
+            .0
+            .clone();
+        let (board, to_move) = match Board::from_fen(&fen) {
+            Ok(position) => position,
+            Err(err) => panic!("can't start from {}: {}", fen, err),
+        };
+
+        app.insert_resource(ChessBoard(board))
+            .insert_resource(TurnState(to_move))
+            .add_startup_system(Self::tilemap_builder)
+            .add_event::<PositionReset>()
+            .add_startup_system_to_stage(StartupStage::PostStartup, Self::setup_pieces)
+            .add_system(Self::setup_pieces.run_on_event::<PositionReset>());
     }
 }
 
@@ -39,17 +152,16 @@ impl BoardPlugin {
     fn tilemap_builder(mut commands: Commands, asset_server: Res<AssetServer>) {
         let texture_handle: Handle<Image> = asset_server.load("tile.png");
         let map_size = TilemapSize { x: 8, y: 8 };
-        let tilemap_entity = commands.spawn_empty().id(); // the entity associated to the tilemap
+        let tilemap_entity = commands.spawn(BoardId::MAIN).id(); // the entity associated to the tilemap
         let mut tile_storage = TileStorage::empty(map_size); // the storage for tiles
 
         for x in 0..map_size.x {
             for y in 0..map_size.y {
-                let white_tile = ((x % 2 == 0) && (y % 2 != 0)) || ((x % 2 != 0) && (y % 2 == 0));
-                let color: TileColor = match white_tile {
+                let tile_pos = TilePos { x, y };
+                let color: TileColor = match is_light_tile(&tile_pos) {
                     true => Color::rgba(255.0, 255.0, 255.0, 1.0).into(),
                     false => Color::rgba(0.0, 0.0, 0.0, 1.0).into(),
                 };
-                let tile_pos = TilePos { x, y };
                 let tile_entity = commands
                     .spawn(TileBundle {
                         color,
@@ -61,6 +173,7 @@ impl BoardPlugin {
                         tile_type: Tile::Empty,
                         piece_ent: None,
                     })
+                    .insert(BoardId::MAIN)
                     .insert(Name::new(format!("Tile ({}, {})", x, y)))
                     .id();
 
@@ -90,261 +203,50 @@ impl BoardPlugin {
         });
     }
 
-    // Spawn the pieces in their correct positions
+    // Spawn the pieces in the positions the board holds, or the one reviewed, in place of the
+    // ones already shown
     fn setup_pieces(
-        mut commands: Commands,
-        game_assets: Res<GameAssets>,
-        tile_storage_q: Query<(&TileStorage, &TilemapGridSize, &TilemapType)>,
-        mut tile_query: Query<(&TilePos, &mut TileState)>,
-        mut meshes: ResMut<Assets<Mesh>>,
-        mut material: ResMut<Assets<ColorMaterial>>,
+        mut spawner: PieceSpawner,
+        board: Res<ChessBoard>,
+        review: Res<ReviewPosition>,
+        tile_storage_q: Query<(&BoardId, &TileStorage, &TilemapGridSize, &TilemapType)>,
+        mut tile_query: Query<(&TilePos, &mut TileState, &BoardId)>,
+        piece_q: Query<(Entity, &BoardId), With<PieceType>>,
     ) {
-        for (tile_storage, grid_size, map_type) in tile_storage_q.iter() {
-            //Blacks
-
-            // spawn black rocks
-            rock::spawn_piece(
-                &mut commands,
-                Team::Black,
-                TilePos { x: 0, y: 7 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.black_rock.clone(),
-                &mut meshes,
-                &mut material,
-            );
-            rock::spawn_piece(
-                &mut commands,
-                Team::Black,
-                TilePos { x: 7, y: 7 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.black_rock.clone(),
-                &mut meshes,
-                &mut material,
-            );
-
-            // spawn black knights
-            knight::spawn_piece(
-                &mut commands,
-                Team::Black,
-                TilePos { x: 1, y: 7 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.black_knight.clone(),
-                &mut meshes,
-                &mut material,
-            );
-            knight::spawn_piece(
-                &mut commands,
-                Team::Black,
-                TilePos { x: 6, y: 7 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.black_knight.clone(),
-                &mut meshes,
-                &mut material,
-            );
-
-            // spawn black bishops
-            bishop::spawn_piece(
-                &mut commands,
-                Team::Black,
-                TilePos { x: 2, y: 7 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.black_bishop.clone(),
-                &mut meshes,
-                &mut material,
-            );
-            bishop::spawn_piece(
-                &mut commands,
-                Team::Black,
-                TilePos { x: 5, y: 7 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.black_bishop.clone(),
-                &mut meshes,
-                &mut material,
-            );
-
-            // spawn black queen
-            queen::spawn_piece(
-                &mut commands,
-                Team::Black,
-                TilePos { x: 3, y: 7 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.black_queen.clone(),
-                &mut meshes,
-                &mut material,
-            );
-
-            // spawn black king
-            king::spawn_piece(
-                &mut commands,
-                Team::Black,
-                TilePos { x: 4, y: 7 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.black_king.clone(),
-                &mut meshes,
-                &mut material,
-            );
-
-            // spawn black pawns
-            for x in 0..8 {
-                pawn::spawn_piece(
-                    &mut commands,
-                    Team::Black,
-                    TilePos { x, y: 6 },
-                    tile_storage,
-                    &mut tile_query,
-                    grid_size,
-                    map_type,
-                    game_assets.black_pawn.clone(),
-                    &mut meshes,
-                    &mut material,
-                );
+        for (piece, board_id) in piece_q.iter() {
+            if *board_id == BoardId::MAIN {
+                spawner.commands.entity(piece).despawn_recursive();
             }
+        }
+        for (_, mut tile_state, board_id) in tile_query.iter_mut() {
+            if *board_id == BoardId::MAIN {
+                tile_state.tile_type = Tile::Empty;
+                tile_state.piece_ent = None;
+            }
+        }
 
-            // WHITES
-
-            // spawn white rocks
-            rock::spawn_piece(
-                &mut commands,
-                Team::White,
-                TilePos { x: 0, y: 0 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.white_rock.clone(),
-                &mut meshes,
-                &mut material,
-            );
-            rock::spawn_piece(
-                &mut commands,
-                Team::White,
-                TilePos { x: 7, y: 0 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.white_rock.clone(),
-                &mut meshes,
-                &mut material,
-            );
-
-            // spawn white knights
-            knight::spawn_piece(
-                &mut commands,
-                Team::White,
-                TilePos { x: 1, y: 0 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.white_knight.clone(),
-                &mut meshes,
-                &mut material,
-            );
-            knight::spawn_piece(
-                &mut commands,
-                Team::White,
-                TilePos { x: 6, y: 0 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.white_knight.clone(),
-                &mut meshes,
-                &mut material,
-            );
-
-            // spawn white bishops
-            bishop::spawn_piece(
-                &mut commands,
-                Team::White,
-                TilePos { x: 2, y: 0 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.white_bishop.clone(),
-                &mut meshes,
-                &mut material,
-            );
-            bishop::spawn_piece(
-                &mut commands,
-                Team::White,
-                TilePos { x: 5, y: 0 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.white_bishop.clone(),
-                &mut meshes,
-                &mut material,
-            );
-
-            // spawn white queen
-            queen::spawn_piece(
-                &mut commands,
-                Team::White,
-                TilePos { x: 3, y: 0 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.white_queen.clone(),
-                &mut meshes,
-                &mut material,
-            );
+        for (board_id, tile_storage, grid_size, map_type) in tile_storage_q.iter() {
+            // the other boards are filled by whatever shows on them
+            if *board_id != BoardId::MAIN {
+                continue;
+            }
 
-            // spawn white king
-            king::spawn_piece(
-                &mut commands,
-                Team::White,
-                TilePos { x: 4, y: 0 },
-                tile_storage,
-                &mut tile_query,
-                grid_size,
-                map_type,
-                game_assets.white_king.clone(),
-                &mut meshes,
-                &mut material,
-            );
+            for (square, piece) in review.shown(&board).pieces() {
+                let spawn_piece = match piece.kind {
+                    PieceKind::Pawn => pawn::spawn_piece,
+                    PieceKind::Rook => rook::spawn_piece,
+                    PieceKind::Knight => knight::spawn_piece,
+                    PieceKind::Bishop => bishop::spawn_piece,
+                    PieceKind::Queen => queen::spawn_piece,
+                    PieceKind::King => king::spawn_piece,
+                };
 
-            // spawn white pawns
-            for x in 0..8 {
-                pawn::spawn_piece(
-                    &mut commands,
-                    Team::White,
-                    TilePos { x, y: 1 },
-                    tile_storage,
+                spawn_piece(
+                    &mut spawner,
+                    piece.team,
+                    to_tile_pos(square),
+                    (tile_storage, grid_size, map_type),
                     &mut tile_query,
-                    grid_size,
-                    map_type,
-                    game_assets.white_pawn.clone(),
-                    &mut meshes,
-                    &mut material,
                 );
             }
         }
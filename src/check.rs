@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use chess_core::{in_check, Team};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    board::{BoardId, ChessBoard},
+    movement::MoveMade,
+    piece::{PieceSystem, PieceType},
+    playback::ReviewPosition,
+};
+
+// the color a king in check is tinted with
+const CHECK_COLOR: Color = Color::rgb(1.0, 0.35, 0.35);
+
+// marks the king that is attacked
+#[derive(Component)]
+pub struct InCheck;
+
+// sent with the team whose king has been attacked by the last move
+pub struct CheckEvent(pub Team);
+
+pub struct CheckPlugin;
+
+impl Plugin for CheckPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CheckEvent>()
+            .add_system(
+                Self::detect_check
+                    .run_on_event::<MoveMade>()
+                    .after(PieceSystem::MovePiece),
+            )
+            .add_system(Self::mark_spawned_king);
+    }
+}
+
+impl CheckPlugin {
+    // kings spawned again, when a move is taken back or a game is opened or reviewed, are tinted
+    // if they stand in check, without telling about a check no move gave
+    fn mark_spawned_king(
+        mut commands: Commands,
+        board: Res<ChessBoard>,
+        review: Res<ReviewPosition>,
+        mut king_q: Query<(Entity, &PieceType, &BoardId, &mut Sprite), Added<PieceType>>,
+    ) {
+        for (king, piece_t, board_id, mut sprite) in king_q.iter_mut() {
+            if !matches!(piece_t, PieceType::King(_)) || *board_id != BoardId::MAIN {
+                continue;
+            }
+
+            if in_check(review.shown(&board), piece_t.get_team()) {
+                commands.entity(king).insert(InCheck);
+                sprite.color = CHECK_COLOR;
+            }
+        }
+    }
+
+    // after every move, marks and tints the king that is attacked and unmarks the other, once
+    // the team in check moves out of it
+    fn detect_check(
+        mut commands: Commands,
+        board: Res<ChessBoard>,
+        mut king_q: Query<(Entity, &PieceType, &BoardId, &mut Sprite, Option<&InCheck>)>,
+        mut check_event: EventWriter<CheckEvent>,
+    ) {
+        for (king, piece_t, board_id, mut sprite, marked) in king_q.iter_mut() {
+            if !matches!(piece_t, PieceType::King(_)) || *board_id != BoardId::MAIN {
+                continue;
+            }
+
+            let team = piece_t.get_team();
+            match (in_check(&board.0, team), marked.is_some()) {
+                (true, false) => {
+                    info!("{:?} is in check", team);
+                    commands.entity(king).insert(InCheck);
+                    sprite.color = CHECK_COLOR;
+                    check_event.send(CheckEvent(team));
+                }
+                (false, true) => {
+                    commands.entity(king).remove::<InCheck>();
+                    sprite.color = Color::WHITE;
+                }
+                _ => {}
+            }
+        }
+    }
+}
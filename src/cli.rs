@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use chess_core::{Board, FenError};
+use clap::Parser;
+
+// what the game can be started with from the command line
+#[derive(Parser, Debug)]
+#[command(version, about = "A chess game made with bevy")]
+pub struct Args {
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Also write the logs to the file, as json"
+    )]
+    pub log_file: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Only show the board, on a see-through window without borders"
+    )]
+    pub overlay: bool,
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Keep the file written with the team to move and the position"
+    )]
+    pub overlay_text: Option<PathBuf>,
+    #[arg(
+        long,
+        value_parser = parse_fen,
+        conflicts_with = "pgn",
+        help = "Start from the position of the FEN instead of the standard one"
+    )]
+    pub fen: Option<String>,
+    #[arg(long, value_name = "PATH", help = "Open the game of the PGN file")]
+    pub pgn: Option<PathBuf>,
+}
+
+// a FEN the game can start from, refused before any window is opened
+fn parse_fen(fen: &str) -> Result<String, FenError> {
+    Board::from_fen(fen)?;
+
+    Ok(fen.to_string())
+}
@@ -0,0 +1,246 @@
+use std::{marker::PhantomData, time::SystemTime};
+
+use ab_glyph::{Font, FontVec, PxScale, ScaleFont};
+use bevy::{ecs::system::SystemParam, prelude::*, render::render_resource::TextureFormat};
+use bevy_egui::{egui, EguiClipboard};
+use chess_core::Square;
+use image::{imageops, RgbaImage};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    board::{is_light_tile, to_tile_pos},
+    hotkeys_enabled,
+    playback::ShownPosition,
+    save::unix_secs,
+    settings::Settings,
+    theme::{Theme, Themes},
+    GameAssets,
+};
+
+// the font of the coordinates when the theme has none, the one egui writes the menus with
+const LABEL_FONT: &str = "Ubuntu-Light";
+
+pub struct ExportPlugin;
+
+// what the exported image is drawn with: the images of the pieces and the colors and font of the
+// theme in use
+#[derive(SystemParam)]
+struct DiagramStyle<'w, 's> {
+    game_assets: Res<'w, GameAssets>,
+    images: Res<'w, Assets<Image>>,
+    themes: Res<'w, Themes>,
+    theme_assets: Res<'w, Assets<Theme>>,
+    #[system_param(ignore)]
+    _marker: PhantomData<&'s ()>,
+}
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(Self::export_board.run_if(hotkeys_enabled))
+            .add_system(Self::copy_diagram.run_if(hotkeys_enabled));
+    }
+}
+
+impl ExportPlugin {
+    // saves the position shown as a png file in the working directory when P is pressed, with the
+    // coordinates and the last move marked, as big as the settings say whatever the size of the
+    // window is
+    fn export_board(
+        keys: Res<Input<KeyCode>>,
+        position: ShownPosition,
+        style: DiagramStyle,
+        settings: Res<Settings>,
+    ) {
+        if !keys.just_pressed(KeyCode::P) {
+            return;
+        }
+
+        // the board is drawn with the theme in use, or black and white until it's loaded
+        let theme = style
+            .themes
+            .handles
+            .get(style.themes.active)
+            .and_then(|handle| style.theme_assets.get(handle));
+        let (light_tile, dark_tile, highlight) = match theme {
+            Some(theme) => (theme.light_tile, theme.dark_tile, theme.highlight),
+            None => (Color::WHITE, Color::BLACK, Color::rgb_u8(0x31, 0x81, 0xC6)),
+        };
+        let last_move = position.last_move().filter(|_| settings.export_last_move);
+        let font = label_font(theme);
+        let tile_size = settings.export_size / 8;
+        let mut diagram = RgbaImage::new(tile_size * 8, tile_size * 8);
+
+        for x in 0..8 {
+            for y in 0..8 {
+                let square = Square::new(x, y);
+                let (color, label_color) = match is_light_tile(&to_tile_pos(square)) {
+                    true => (light_tile, dark_tile),
+                    false => (dark_tile, light_tile),
+                };
+                let mut color = to_rgba8(color);
+                if last_move.is_some_and(|mv| mv.from == square || mv.to == square) {
+                    color = mix(color, to_rgba8(highlight), 0.5);
+                }
+                // the image is drawn from the top, where black's side is
+                let (left, top) = (x * tile_size, (7 - y) * tile_size);
+
+                for px in left..left + tile_size {
+                    for py in top..top + tile_size {
+                        diagram.put_pixel(px, py, image::Rgba(color));
+                    }
+                }
+
+                // the ranks are numbered along the a-file and the files lettered along the first
+                // rank, in the corners the pieces leave free
+                if let Some(font) = &font {
+                    let label_color = to_rgba8(label_color);
+                    if x == 0 {
+                        let rank = char::from(b'1' + y as u8);
+                        draw_label(
+                            &mut diagram,
+                            font,
+                            rank,
+                            (left, top),
+                            tile_size,
+                            label_color,
+                        );
+                    }
+                    if y == 0 {
+                        let file = char::from(b'a' + x as u8);
+                        draw_label(
+                            &mut diagram,
+                            font,
+                            file,
+                            (left, top),
+                            tile_size,
+                            label_color,
+                        );
+                    }
+                }
+
+                if let Some(piece) = position.board().piece_at(square) {
+                    match style
+                        .images
+                        .get(&style.game_assets.piece_image(piece))
+                        .and_then(to_rgba_image)
+                    {
+                        Some(sprite) => {
+                            let sprite = imageops::resize(
+                                &sprite,
+                                tile_size,
+                                tile_size,
+                                imageops::FilterType::Nearest,
+                            );
+                            imageops::overlay(&mut diagram, &sprite, left as i64, top as i64);
+                        }
+                        None => warn!("The image of a {:?} isn't loaded yet", piece),
+                    }
+                }
+            }
+        }
+
+        let path = format!("position-{}.png", unix_secs(SystemTime::now()));
+
+        match diagram.save(&path) {
+            Ok(()) => info!("Position exported to {}", path),
+            Err(e) => error!("Couldn't export the position: {}", e),
+        }
+    }
+
+    // copies a text drawing of the position shown to the clipboard when C is pressed, with letters for
+    // the pieces, or with unicode symbols if shift is held
+    fn copy_diagram(
+        keys: Res<Input<KeyCode>>,
+        position: ShownPosition,
+        mut clipboard: ResMut<EguiClipboard>,
+    ) {
+        if !keys.just_pressed(KeyCode::C) {
+            return;
+        }
+
+        let unicode = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+        clipboard.set_contents(&position.board().diagram(unicode));
+        info!("Position copied to the clipboard");
+    }
+}
+
+// the font of the theme, or the one egui ships with. none if the theme's can't be read or egui's
+// was left out of the build
+fn label_font(theme: Option<&Theme>) -> Option<FontVec> {
+    let data = match theme.and_then(|theme| theme.font.clone()) {
+        Some(font) => font,
+        None => egui::FontDefinitions::default()
+            .font_data
+            .get(LABEL_FONT)?
+            .font
+            .to_vec(),
+    };
+
+    FontVec::try_from_vec(data).ok()
+}
+
+// writes a rank number in the top left corner of the tile, or a file letter in its bottom right
+// one
+fn draw_label(
+    diagram: &mut RgbaImage,
+    font: &FontVec,
+    label: char,
+    (left, top): (u32, u32),
+    tile_size: u32,
+    color: [u8; 4],
+) {
+    let scale = PxScale::from(tile_size as f32 / 4.0);
+    let scaled = font.as_scaled(scale);
+    let margin = tile_size as f32 / 16.0;
+    let glyph_id = font.glyph_id(label);
+    let position = match label.is_ascii_digit() {
+        true => ab_glyph::point(left as f32 + margin, top as f32 + margin + scaled.ascent()),
+        false => ab_glyph::point(
+            (left + tile_size) as f32 - margin - scaled.h_advance(glyph_id),
+            (top + tile_size) as f32 - margin + scaled.descent(),
+        ),
+    };
+    let glyph = match font.outline_glyph(glyph_id.with_scale_and_position(scale, position)) {
+        Some(glyph) => glyph,
+        None => return,
+    };
+
+    let bounds = glyph.px_bounds();
+    glyph.draw(|x, y, coverage| {
+        let (px, py) = (bounds.min.x as u32 + x, bounds.min.y as u32 + y);
+        if px < diagram.width() && py < diagram.height() {
+            let pixel = diagram.get_pixel_mut(px, py);
+            pixel.0 = mix(pixel.0, color, coverage);
+        }
+    });
+}
+
+// the color part of the way from one color to the other
+fn mix(from: [u8; 4], to: [u8; 4], part: f32) -> [u8; 4] {
+    let mut mixed = from;
+    for (channel, target) in mixed.iter_mut().zip(to) {
+        *channel =
+            (*channel as f32 + (target as f32 - *channel as f32) * part.clamp(0.0, 1.0)) as u8;
+    }
+
+    mixed
+}
+
+fn to_rgba8(color: Color) -> [u8; 4] {
+    color
+        .as_rgba_f32()
+        .map(|c| (c.clamp(0.0, 1.0) * 255.0) as u8)
+}
+
+// copies the pixels of a loaded texture, only 8 bits rgba textures, which png files are loaded
+// as, are supported
+fn to_rgba_image(image: &Image) -> Option<RgbaImage> {
+    match image.texture_descriptor.format {
+        TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm => RgbaImage::from_raw(
+            image.texture_descriptor.size.width,
+            image.texture_descriptor.size.height,
+            image.data.clone(),
+        ),
+        _ => None,
+    }
+}
@@ -0,0 +1,325 @@
+use std::{fs, time::SystemTime};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use bevy_mod_picking::PickingPluginsState;
+use chess_core::{outcome, Board, Outcome, Pgn, Team, STARTING_FEN};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    ai::COMPUTER_TEAM,
+    board::{ChessBoard, PositionReset, StartingPosition},
+    history::MoveHistory,
+    hot_seat::HotSeat,
+    menus_shown,
+    movement::MoveMade,
+    overlay::Overlay,
+    piece::{PieceSystem, TurnState},
+    playback::Playback,
+    promotion::PendingPromotion,
+    save::unix_secs,
+    settings::Settings,
+    undo::{RedoMove, TakeBack},
+};
+
+// what the app is showing, the board can only be picked while the game is played
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameState {
+    MainMenu,
+    Playing,
+    // the game is stopped, with its clocks and the computer's turn
+    Paused,
+    // a pawn reached the last rank and waits to know what it turns into
+    Promotion,
+    GameOver,
+    // the moves of a game opened from a file, or of the game just ended, are stepped through
+    Review,
+}
+
+// how the game ended, inserted when it does
+#[derive(Resource, Debug)]
+pub struct GameResult(pub Outcome);
+
+// the team that offered a draw, until the other team answers or moves
+#[derive(Resource, Default, Debug)]
+pub struct DrawOffer(pub Option<Team>);
+
+// sent to start the game again from the position it started from
+pub struct NewGame;
+
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        // the overlay has no main menu to start the game from
+        let first_state = match app.world.contains_resource::<Overlay>() {
+            true => GameState::Playing,
+            false => GameState::MainMenu,
+        };
+
+        app.add_loopless_state(first_state)
+            .init_resource::<DrawOffer>()
+            .add_event::<NewGame>()
+            .add_enter_system(GameState::Playing, Self::unfreeze_board)
+            .add_exit_system(GameState::Playing, Self::freeze_board)
+            .add_system(
+                Self::game_controls
+                    .run_in_state(GameState::Playing)
+                    .run_if(menus_shown),
+            )
+            .add_system(Self::expire_draw_offer.run_on_event::<MoveMade>())
+            .add_system(Self::start_new_game.run_on_event::<NewGame>())
+            .add_system(
+                Self::detect_game_over
+                    .run_in_state(GameState::Playing)
+                    .run_on_event::<MoveMade>()
+                    .after(PieceSystem::MovePiece),
+            )
+            // a game can also start, or be taken back to, a position where it's already over
+            .add_enter_system(GameState::Playing, Self::detect_game_over)
+            .add_system(
+                Self::detect_game_over
+                    .run_in_state(GameState::Playing)
+                    .run_on_event::<PositionReset>(),
+            )
+            .add_system(Self::game_over_screen.run_in_state(GameState::GameOver));
+    }
+}
+
+impl GamePlugin {
+    // ends the game once the team to move has no move left, after a move or once the pieces
+    // are set up from a position
+    fn detect_game_over(mut commands: Commands, board: Res<ChessBoard>, turn: Res<TurnState>) {
+        if let Some(result) = outcome(&board.0, turn.0) {
+            info!("Game over: {:?}", result);
+            commands.insert_resource(GameResult(result));
+            commands.insert_resource(NextState(GameState::GameOver));
+        }
+    }
+
+    // lets the player resign, offer a draw or take back and redo moves, and answer the offers of
+    // the other player. two players sharing the board act for the team to move, against the
+    // computer the player only acts for their own team, as the computer answers offers itself
+    fn game_controls(
+        mut commands: Commands,
+        mut egui_context: ResMut<EguiContext>,
+        (turn, settings): (Res<TurnState>, Res<Settings>),
+        history: Res<MoveHistory>,
+        mut offer: ResMut<DrawOffer>,
+        mut take_back: EventWriter<TakeBack>,
+        mut redo: EventWriter<RedoMove>,
+    ) {
+        let player = match settings.vs_computer {
+            true => COMPUTER_TEAM.opposite(),
+            false => turn.0,
+        };
+        let mut result = None;
+
+        egui::Window::new("Game")
+            .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-8.0, 8.0))
+            .collapsible(false)
+            .resizable(false)
+            .show(egui_context.ctx_mut(), |ui| match offer.0 {
+                // the offer is waiting for the computer, or the other player, to answer it
+                Some(team) if team == player => {
+                    ui.label(format!("{:?} offers a draw", team));
+                }
+                Some(team) => {
+                    ui.label(format!("{:?} offers a draw", team));
+                    ui.horizontal(|ui| {
+                        if ui.button("Accept").clicked() {
+                            result = Some(Outcome::DrawAgreed);
+                        }
+                        if ui.button("Decline").clicked() {
+                            offer.0 = None;
+                        }
+                    });
+                }
+                None => {
+                    ui.horizontal(|ui| {
+                        if ui.button("Resign").clicked() {
+                            result = Some(Outcome::Resignation {
+                                winner: player.opposite(),
+                            });
+                        }
+                        if ui.button("Offer a draw").clicked() {
+                            offer.0 = Some(player);
+                        }
+                        let undo = egui::Button::new("Undo");
+                        if ui.add_enabled(!history.moves.is_empty(), undo).clicked() {
+                            take_back.send(TakeBack);
+                        }
+                        let redo_button = egui::Button::new("Redo");
+                        if ui
+                            .add_enabled(!history.redos.is_empty(), redo_button)
+                            .clicked()
+                        {
+                            redo.send(RedoMove);
+                        }
+                    });
+                }
+            });
+
+        if let Some(result) = result {
+            end_game(&mut commands, &mut offer, result);
+        }
+    }
+
+    // moving instead of answering a draw offer declines it
+    fn expire_draw_offer(mut events: EventReader<MoveMade>, mut offer: ResMut<DrawOffer>) {
+        for event in events.iter() {
+            if offer.0 == Some(event.team.opposite()) {
+                offer.0 = None;
+            }
+        }
+    }
+
+    // the board stays hidden, and frozen, until the device is passed on in hot seat
+    fn unfreeze_board(hot_seat: Res<HotSeat>, mut picking: ResMut<PickingPluginsState>) {
+        picking.enable_picking = !hot_seat.hidden();
+    }
+
+    fn freeze_board(mut picking: ResMut<PickingPluginsState>) {
+        picking.enable_picking = false;
+    }
+
+    // sets the pieces back where the game started and plays it from the first move again,
+    // whatever was going on. the pieces, the tiles and the selection are reset by PositionReset
+    fn start_new_game(
+        mut commands: Commands,
+        position: Res<StartingPosition>,
+        mut board: ResMut<ChessBoard>,
+        mut turn: ResMut<TurnState>,
+        mut offer: ResMut<DrawOffer>,
+        mut reset: EventWriter<PositionReset>,
+    ) {
+        let (start, to_move) =
+            Board::from_fen(&position.0).expect("the starting position is read at startup");
+
+        info!("New game");
+        commands.insert_resource(MoveHistory::new(&start, to_move));
+        commands.remove_resource::<GameResult>();
+        commands.remove_resource::<PendingPromotion>();
+        commands.remove_resource::<Playback>();
+        commands.insert_resource(NextState(GameState::Playing));
+        board.0 = start;
+        turn.0 = to_move;
+        offer.0 = None;
+        reset.send(PositionReset);
+    }
+
+    // dims the board behind the result, how the game ended and what can be done next
+    fn game_over_screen(
+        mut commands: Commands,
+        mut egui_context: ResMut<EguiContext>,
+        result: Res<GameResult>,
+        history: Res<MoveHistory>,
+        mut new_game: EventWriter<NewGame>,
+    ) {
+        let score = match result.0.winner() {
+            Some(_) => result.0.result(),
+            None => "½-½",
+        };
+
+        egui::CentralPanel::default()
+            .frame(egui::Frame::none().fill(egui::Color32::from_black_alpha(160)))
+            .show(egui_context.ctx_mut(), |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(ui.available_height() / 3.0);
+                    ui.heading(score);
+                    ui.label(termination(result.0));
+                    ui.add_space(8.0);
+
+                    if ui.button("Rematch").clicked() {
+                        new_game.send(NewGame);
+                    }
+                    if ui.button("Review").clicked() {
+                        commands.insert_resource(Playback::from_game(&history, result.0));
+                        commands.insert_resource(NextState(GameState::Review));
+                    }
+                    if ui.button("Export PGN").clicked() {
+                        export_pgn(&history, result.0);
+                    }
+                });
+            });
+    }
+}
+
+// ends the game with the result given, by a player or the computer rather than by the rules
+pub fn end_game(commands: &mut Commands, offer: &mut DrawOffer, result: Outcome) {
+    info!("Game over: {:?}", result);
+    offer.0 = None;
+    commands.insert_resource(GameResult(result));
+    commands.insert_resource(NextState(GameState::GameOver));
+}
+
+// how the game ended, in words
+fn termination(outcome: Outcome) -> String {
+    match outcome {
+        Outcome::Checkmate { winner } => format!("Checkmate, {:?} wins", winner),
+        Outcome::Stalemate => "Stalemate, the game is drawn".to_string(),
+        Outcome::FiftyMoveRule => {
+            "Fifty moves without a capture or a pawn move, the game is drawn".to_string()
+        }
+        Outcome::InsufficientMaterial => {
+            "Neither team can give mate, the game is drawn".to_string()
+        }
+        Outcome::Resignation { winner } => {
+            format!("{:?} resigns, {:?} wins", winner.opposite(), winner)
+        }
+        Outcome::DrawAgreed => "The game is drawn by agreement".to_string(),
+    }
+}
+
+// the Termination tag, which only takes the values the PGN standard lists: every way a game can
+// end here is a "normal" one, the reason is written in a comment after the moves
+fn termination_tag(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::Checkmate { .. }
+        | Outcome::Stalemate
+        | Outcome::FiftyMoveRule
+        | Outcome::InsufficientMaterial
+        | Outcome::Resignation { .. }
+        | Outcome::DrawAgreed => "normal",
+    }
+}
+
+// the game as PGN, with the tags every game has and where it started from if it isn't the
+// standard position
+pub fn game_pgn(history: &MoveHistory, outcome: Outcome) -> Pgn {
+    let mut tags = vec![
+        ("Event", "Casual game".to_string()),
+        ("Site", "bevy-chess".to_string()),
+        ("Date", "????.??.??".to_string()),
+        ("Round", "-".to_string()),
+        ("White", "?".to_string()),
+        ("Black", "?".to_string()),
+        ("Result", outcome.result().to_string()),
+        ("Termination", termination_tag(outcome).to_string()),
+    ];
+    if history.starting_fen != STARTING_FEN {
+        tags.push(("SetUp", "1".to_string()));
+        tags.push(("FEN", history.starting_fen.clone()));
+    }
+
+    Pgn {
+        tags: tags
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+        moves: history.san_moves(),
+        result: Some(outcome.result().to_string()),
+        comment: Some(termination(outcome)),
+    }
+}
+
+// writes the game to a PGN file in the working directory
+fn export_pgn(history: &MoveHistory, outcome: Outcome) {
+    let pgn = game_pgn(history, outcome);
+    let path = format!("game-{}.pgn", unix_secs(SystemTime::now()));
+
+    match fs::write(&path, pgn.to_string()) {
+        Ok(()) => info!("Game exported to {}", path),
+        Err(e) => error!("Couldn't export the game: {}", e),
+    }
+}
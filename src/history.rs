@@ -0,0 +1,122 @@
+use std::time::{Duration, SystemTime};
+
+use bevy::prelude::*;
+use chess_core::{can_move, in_check, Board, Move, Piece, Team, Undo};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    board::ChessBoard,
+    movement::MoveMade,
+    piece::{PieceSystem, TurnState},
+};
+
+// what a move did besides taking a piece, worked out once it's made
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct MoveFlags {
+    pub check: bool,
+    pub checkmate: bool,
+    // the pawn taken wasn't on the square the move ended on
+    pub en_passant: bool,
+}
+
+// a move of the game as it's remembered, with everything that shows, writes or takes it back
+// asking for it here instead of working it out again
+#[derive(Clone, Debug)]
+pub struct MoveRecord {
+    // where the piece went from and to, and what it turned into
+    pub mv: Move,
+    // the piece that moved, the pawn when it was promoted
+    pub piece: Piece,
+    pub captured: Option<Piece>,
+    pub flags: MoveFlags,
+    pub san: String,
+    // when the move was made, by the wall clock and by the time the app has been running
+    pub played_at: SystemTime,
+    pub elapsed: Duration,
+    pub undo: Undo,
+}
+
+impl MoveRecord {
+    // the move just made, with what's needed to take it back, and the board standing after it,
+    // which tells whether it gave check or mate
+    pub fn new(undo: Undo, san: String, after: &Board, elapsed: Duration) -> Self {
+        let opponent = undo.piece.team.opposite();
+        let check = in_check(after, opponent);
+
+        Self {
+            mv: undo.mv,
+            piece: undo.piece,
+            captured: undo.captured,
+            flags: MoveFlags {
+                check,
+                checkmate: check && !can_move(after, opponent),
+                en_passant: undo.captured.is_some() && undo.captured_square != undo.mv.to,
+            },
+            san,
+            played_at: SystemTime::now(),
+            elapsed,
+            undo,
+        }
+    }
+}
+
+// the moves of the game being played and where it started from, the single place the rest of
+// the app reads the game from. the moves taken back are kept too, the last one on top, until a
+// move other than the one on top is made
+#[derive(Resource, Debug)]
+pub struct MoveHistory {
+    pub starting_fen: String,
+    pub moves: Vec<MoveRecord>,
+    pub redos: Vec<Move>,
+}
+
+impl MoveHistory {
+    pub fn new(board: &Board, to_move: Team) -> Self {
+        Self {
+            starting_fen: board.to_fen(to_move),
+            moves: Vec::new(),
+            redos: Vec::new(),
+        }
+    }
+
+    // the moves in standard algebraic notation, as PGN writes them
+    pub fn san_moves(&self) -> Vec<String> {
+        self.moves.iter().map(|record| record.san.clone()).collect()
+    }
+}
+
+impl FromWorld for MoveHistory {
+    fn from_world(world: &mut World) -> Self {
+        Self::new(
+            &world.resource::<ChessBoard>().0,
+            world.resource::<TurnState>().0,
+        )
+    }
+}
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MoveHistory>().add_system(
+            Self::record_move
+                .run_on_event::<MoveMade>()
+                .after(PieceSystem::MovePiece),
+        );
+    }
+}
+
+impl HistoryPlugin {
+    fn record_move(
+        mut events: EventReader<MoveMade>,
+        time: Res<Time>,
+        board: Res<ChessBoard>,
+        mut history: ResMut<MoveHistory>,
+    ) {
+        for event in events.iter() {
+            let record = MoveRecord::new(event.undo, event.san.clone(), &board.0, time.elapsed());
+
+            history.moves.push(record);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use bevy_mod_picking::PickingPluginsState;
+use chess_core::Team;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    accessibility::Magnifier,
+    board::TILE_SIZE,
+    game::{GameState, NewGame},
+    hotkeys_enabled,
+    movement::MoveMade,
+    piece::{PieceType, TurnState},
+    tray::CapturedPiece,
+};
+
+// where the squares the pieces stand on are centered, the board is turned around this point
+const BOARD_CENTER: f32 = TILE_SIZE * 3.5;
+
+// the camera looking at the board, the lens takes its rotation from it
+type BoardCameraQuery<'w, 's> =
+    Query<'w, 's, &'static mut Transform, (With<Camera2d>, Without<Magnifier>)>;
+// the sprites kept upright when the board is turned around
+type UprightQuery<'w, 's> =
+    Query<'w, 's, &'static mut Transform, Or<(With<PieceType>, With<CapturedPiece>)>>;
+// the upright sprites spawned since the board was last turned
+type AddedUprightQuery<'w, 's> = Query<'w, 's, (), Or<(Added<PieceType>, Added<CapturedPiece>)>>;
+
+// for two players sharing one device: once a move is made the board is hidden until the other
+// player has the device and says so, and the board is turned to face the team to move
+#[derive(Resource, Default)]
+pub struct HotSeat {
+    pub enabled: bool,
+    hidden: bool,
+}
+
+impl HotSeat {
+    // wether the board is hidden until the other player has the device
+    pub fn hidden(&self) -> bool {
+        self.hidden
+    }
+}
+
+pub struct HotSeatPlugin;
+
+impl Plugin for HotSeatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HotSeat>()
+            .add_system(Self::toggle_hot_seat.run_if(hotkeys_enabled))
+            .add_system(Self::show_board.run_on_event::<NewGame>())
+            .add_system(
+                Self::hide_board
+                    .run_in_state(GameState::Playing)
+                    .run_on_event::<MoveMade>(),
+            )
+            // the board stays frozen once the game is over
+            .add_system(Self::pass_device_screen.run_in_state(GameState::Playing))
+            .add_system(Self::face_team_to_move);
+    }
+}
+
+impl HotSeatPlugin {
+    // turns hot seat on and off when H is pressed. the board hidden for the other player is shown
+    // right away, and can be picked again if the game is being played
+    fn toggle_hot_seat(
+        keys: Res<Input<KeyCode>>,
+        state: Res<CurrentState<GameState>>,
+        mut hot_seat: ResMut<HotSeat>,
+        mut picking: ResMut<PickingPluginsState>,
+    ) {
+        if !keys.just_pressed(KeyCode::H) {
+            return;
+        }
+
+        if hot_seat.hidden && state.0 == GameState::Playing {
+            picking.enable_picking = true;
+        }
+        hot_seat.enabled = !hot_seat.enabled;
+        hot_seat.hidden = false;
+        match hot_seat.enabled {
+            true => info!("Hot seat on"),
+            false => info!("Hot seat off"),
+        }
+    }
+
+    // a new game starts with the board shown to white
+    fn show_board(mut hot_seat: ResMut<HotSeat>) {
+        hot_seat.hidden = false;
+    }
+
+    fn hide_board(mut hot_seat: ResMut<HotSeat>, mut picking: ResMut<PickingPluginsState>) {
+        if hot_seat.enabled {
+            hot_seat.hidden = true;
+            picking.enable_picking = false;
+        }
+    }
+
+    // covers the whole window while the board is hidden, until the button or space is pressed
+    fn pass_device_screen(
+        mut egui_context: ResMut<EguiContext>,
+        keys: Res<Input<KeyCode>>,
+        turn: Res<TurnState>,
+        mut hot_seat: ResMut<HotSeat>,
+        mut picking: ResMut<PickingPluginsState>,
+    ) {
+        if !hot_seat.hidden {
+            return;
+        }
+
+        let mut ready = keys.just_pressed(KeyCode::Space);
+        egui::CentralPanel::default()
+            .frame(egui::Frame::none().fill(egui::Color32::from_gray(38)))
+            .show(egui_context.ctx_mut(), |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(ui.available_height() / 3.0);
+                    ui.heading(format!("{:?} to move", turn.0));
+                    ui.label("Pass the device to the other player");
+                    ui.add_space(8.0);
+                    ready |= ui.button("Show the board").clicked();
+                });
+            });
+
+        if ready {
+            hot_seat.hidden = false;
+            picking.enable_picking = true;
+        }
+    }
+
+    // looks at the board from black's side when it's black's turn, turning the pieces, captured
+    // ones and the ones spawned again too, so they stay upright
+    fn face_team_to_move(
+        hot_seat: Res<HotSeat>,
+        turn: Res<TurnState>,
+        // bevy can't tell the filters of the two queries never match the same entity, so they
+        // take turns
+        mut transform_qs: ParamSet<(BoardCameraQuery, UprightQuery)>,
+        added_q: AddedUprightQuery,
+    ) {
+        if !hot_seat.is_changed() && !turn.is_changed() && added_q.is_empty() {
+            return;
+        }
+
+        let flip = hot_seat.enabled && turn.0 == Team::Black;
+        let rotation = match flip {
+            true => Quat::from_rotation_z(PI),
+            false => Quat::IDENTITY,
+        };
+
+        for mut camera_t in transform_qs.p0().iter_mut() {
+            let flipped = camera_t.rotation != Quat::IDENTITY;
+            if flipped == flip {
+                continue;
+            }
+
+            // the camera is mirrored through the center of the board, so it keeps framing it
+            // the same way
+            camera_t.translation.x = 2.0 * BOARD_CENTER - camera_t.translation.x;
+            camera_t.translation.y = 2.0 * BOARD_CENTER - camera_t.translation.y;
+            camera_t.rotation = rotation;
+        }
+
+        for mut piece_t in transform_qs.p1().iter_mut() {
+            piece_t.rotation = rotation;
+        }
+    }
+}
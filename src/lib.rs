@@ -0,0 +1,235 @@
+#![doc = include_str!("../README.md")]
+// the game is also a library, so other bevy plugins (a custom hud, a stats recorder...) can be
+// added next to it. only what the prelude exports is kept stable between minor versions, the
+// rest may change with any release
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy_mod_picking::PickingCameraBundle;
+
+mod accessibility;
+mod ai;
+mod bishop;
+mod board;
+mod check;
+pub mod cli;
+mod export;
+mod game;
+mod history;
+mod hot_seat;
+mod king;
+mod knight;
+mod logging;
+mod menu;
+mod movement;
+mod overlay;
+mod pawn;
+mod piece;
+mod playback;
+mod power;
+mod promotion;
+mod queen;
+mod report;
+mod rook;
+mod save;
+mod settings;
+mod theme;
+mod tray;
+mod undo;
+use accessibility::AccessibilityPlugin;
+use ai::AiPlugin;
+use bevy_egui::{EguiContext, EguiPlugin};
+use board::{BoardPlugin, TILE_SIZE};
+use check::CheckPlugin;
+use chess_core::{Piece, PieceKind, Team};
+use export::ExportPlugin;
+use game::GamePlugin;
+use history::HistoryPlugin;
+use hot_seat::HotSeatPlugin;
+pub use logging::{FileLogPlugin, LogFile};
+use menu::MenuPlugin;
+use overlay::Overlay;
+pub use overlay::OverlayTextPlugin;
+use piece::PiecePlugin;
+use playback::PlaybackPlugin;
+use power::PowerSavingPlugin;
+use promotion::PromotionPlugin;
+use report::ReportPlugin;
+use save::SavePlugin;
+use settings::SettingsPlugin;
+use theme::ThemePlugin;
+use tray::CapturedTrayPlugin;
+use undo::UndoPlugin;
+
+pub mod prelude {
+    pub use crate::{
+        ai::{Thinking, COMPUTER_TEAM},
+        bishop::Bishop,
+        board::{BoardId, ChessBoard, PositionReset, StartingPosition, Tile, TileState},
+        check::{CheckEvent, InCheck},
+        game::{DrawOffer, GameResult, GameState, NewGame},
+        history::{MoveFlags, MoveHistory, MoveRecord},
+        hot_seat::HotSeat,
+        king::King,
+        knight::Knight,
+        movement::{MoveMade, MoveRequested, MoveValidator},
+        overlay::Overlay,
+        pawn::Pawn,
+        piece::{
+            HighLight, PieceCaptured, PieceDeathEvent, PieceSystem, PieceType, SelectedPiece,
+            TouchedPiece, TurnState,
+        },
+        playback::{Playback, ReviewPosition},
+        promotion::PendingPromotion,
+        queen::Queen,
+        rook::Rook,
+        save::{DataDir, LastGame, LoadGame, SavedGame, SavedMove},
+        settings::Settings,
+        tray::CapturedPieces,
+        undo::{RedoMove, TakeBack},
+        BevyChessPlugin,
+    };
+    pub use chess_core::{
+        best_move, best_move_within, can_move, evaluate, in_check, insufficient_material,
+        is_attacked, is_legal, is_promotion, king_square, legal_moves, outcome, parse_san, perft,
+        pin_ray, team_moves, to_san, Bitboards, Board, FenError, Move, Outcome, Pgn, PgnError,
+        Piece, PieceKind, Square, Team, Undo, PROMOTIONS, STARTING_FEN,
+    };
+}
+
+// the whole game, it needs bevy's DefaultPlugins, bevy_ecs_tilemap's TilemapPlugin and
+// bevy_mod_picking's DefaultPickingPlugins to be added too
+pub struct BevyChessPlugin;
+
+impl Plugin for BevyChessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_camera)
+            .add_startup_system_to_stage(StartupStage::PreStartup, asset_loader)
+            .add_plugin(SettingsPlugin)
+            .add_plugin(BoardPlugin)
+            .add_plugin(PiecePlugin)
+            .add_plugin(CheckPlugin)
+            .add_plugin(GamePlugin)
+            .add_plugin(HistoryPlugin)
+            .add_plugin(MenuPlugin)
+            .add_plugin(PromotionPlugin)
+            .add_plugin(PlaybackPlugin)
+            .add_plugin(UndoPlugin)
+            .add_plugin(SavePlugin)
+            .add_plugin(AiPlugin)
+            .add_plugin(CapturedTrayPlugin)
+            .add_plugin(ThemePlugin)
+            .add_plugin(ExportPlugin)
+            .add_plugin(PowerSavingPlugin)
+            .add_plugin(ReportPlugin)
+            .add_plugin(HotSeatPlugin)
+            .add_plugin(AccessibilityPlugin);
+
+        // the inspector may have added it already
+        if !app.world.contains_resource::<EguiContext>() {
+            app.add_plugin(EguiPlugin);
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct GameAssets {
+    white_pawn: Handle<Image>,
+    white_rook: Handle<Image>,
+    white_bishop: Handle<Image>,
+    white_knight: Handle<Image>,
+    white_queen: Handle<Image>,
+    white_king: Handle<Image>,
+    black_pawn: Handle<Image>,
+    black_rook: Handle<Image>,
+    black_knight: Handle<Image>,
+    black_bishop: Handle<Image>,
+    black_queen: Handle<Image>,
+    black_king: Handle<Image>,
+}
+
+impl GameAssets {
+    // the piece images of a folder of the assets, named as the ones at the root of it
+    pub(crate) fn load(asset_server: &AssetServer, folder: &str) -> Self {
+        let image = |name: &str| asset_server.load(Path::new(folder).join(name));
+
+        Self {
+            white_pawn: image("white_pawn.png"),
+            white_rook: image("white_rook.png"),
+            white_knight: image("white_knight.png"),
+            white_bishop: image("white_bishop.png"),
+            white_queen: image("white_queen.png"),
+            white_king: image("white_king.png"),
+            black_pawn: image("black_pawn.png"),
+            black_rook: image("black_rook.png"),
+            black_knight: image("black_knight.png"),
+            black_bishop: image("black_bishop.png"),
+            black_queen: image("black_queen.png"),
+            black_king: image("black_king.png"),
+        }
+    }
+
+    // every image, in the same order whatever the set is
+    pub(crate) fn images(&self) -> [&Handle<Image>; 12] {
+        [
+            &self.white_pawn,
+            &self.white_rook,
+            &self.white_knight,
+            &self.white_bishop,
+            &self.white_queen,
+            &self.white_king,
+            &self.black_pawn,
+            &self.black_rook,
+            &self.black_knight,
+            &self.black_bishop,
+            &self.black_queen,
+            &self.black_king,
+        ]
+    }
+
+    // the image a piece is drawn with
+    pub fn piece_image(&self, piece: Piece) -> Handle<Image> {
+        let image = match (piece.team, piece.kind) {
+            (Team::White, PieceKind::Pawn) => &self.white_pawn,
+            (Team::White, PieceKind::Rook) => &self.white_rook,
+            (Team::White, PieceKind::Knight) => &self.white_knight,
+            (Team::White, PieceKind::Bishop) => &self.white_bishop,
+            (Team::White, PieceKind::Queen) => &self.white_queen,
+            (Team::White, PieceKind::King) => &self.white_king,
+            (Team::Black, PieceKind::Pawn) => &self.black_pawn,
+            (Team::Black, PieceKind::Rook) => &self.black_rook,
+            (Team::Black, PieceKind::Knight) => &self.black_knight,
+            (Team::Black, PieceKind::Bishop) => &self.black_bishop,
+            (Team::Black, PieceKind::Queen) => &self.black_queen,
+            (Team::Black, PieceKind::King) => &self.black_king,
+        };
+
+        image.clone()
+    }
+}
+
+// run condition of the single key shortcuts, which are left alone while a text field of the menus
+// is typed in
+pub(crate) fn hotkeys_enabled(mut egui_context: ResMut<EguiContext>) -> bool {
+    !egui_context.ctx_mut().wants_keyboard_input()
+}
+
+// run condition of the menus, which are left out of the overlay window
+pub(crate) fn menus_shown(overlay: Option<Res<Overlay>>) -> bool {
+    overlay.is_none()
+}
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn((
+        Camera2dBundle {
+            transform: Transform::from_xyz(TILE_SIZE * 4.0, TILE_SIZE * 4.0, 999.9),
+            ..default()
+        },
+        PickingCameraBundle::default(),
+    ));
+}
+
+fn asset_loader(mut commands: Commands, asset_server: Res<AssetServer>) {
+    // the theme picked may draw them with another set once it's loaded
+    commands.insert_resource(GameAssets::load(&asset_server, ""));
+}
@@ -0,0 +1,52 @@
+use std::{fs::File, io, path::PathBuf, sync::Arc};
+
+use bevy::{log::Level, prelude::*};
+use tracing_log::LogTracer;
+use tracing_subscriber::{fmt::format::FmtSpan, prelude::*, EnvFilter, Registry};
+
+// takes the place of bevy's LogPlugin when the game is started with `--log-file <path>`: the
+// console output stays the same, and every event, together with the time spent in each span, is
+// also written to the file as one json object per line
+pub struct FileLogPlugin {
+    path: PathBuf,
+    file: Arc<File>,
+}
+
+impl FileLogPlugin {
+    // the file is created here, so a path it can't be created at is refused before the app is
+    // built
+    pub fn new(path: PathBuf) -> io::Result<Self> {
+        let file = File::create(&path)?;
+
+        Ok(Self {
+            path,
+            file: Arc::new(file),
+        })
+    }
+}
+
+// where the logs are being written to, when they are
+#[derive(Resource)]
+pub struct LogFile(pub PathBuf);
+
+impl Plugin for FileLogPlugin {
+    fn build(&self, app: &mut App) {
+        LogTracer::init().unwrap();
+        // the same filter bevy's LogPlugin uses, which RUST_LOG overrides
+        let filter_layer = EnvFilter::try_from_default_env()
+            .or_else(|_| EnvFilter::try_new(format!("{},wgpu=error", Level::INFO)))
+            .unwrap();
+        let file_layer = tracing_subscriber::fmt::layer()
+            .json()
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer(self.file.clone());
+        let subscriber = Registry::default()
+            .with(filter_layer)
+            .with(tracing_subscriber::fmt::layer())
+            .with(file_layer);
+
+        bevy::utils::tracing::subscriber::set_global_default(subscriber)
+            .expect("Could not set the global tracing subscriber");
+        app.insert_resource(LogFile(self.path.clone()));
+    }
+}
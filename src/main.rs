@@ -1,46 +1,44 @@
-#![doc = include_str!("../README.md")]
-use bevy::prelude::*;
+use bevy::{log::LogPlugin, prelude::*};
+use bevy_chess::{cli::Args, prelude::*, FileLogPlugin, OverlayTextPlugin};
 use bevy_ecs_tilemap::TilemapPlugin;
 use bevy_inspector_egui::WorldInspectorPlugin;
-use bevy_mod_picking::{DefaultPickingPlugins, PickingCameraBundle};
-
-mod bishop;
-mod board;
-mod king;
-mod knight;
-mod movement;
-mod pawn;
-mod piece;
-mod queen;
-mod rock;
-use board::{BoardPlugin, TILE_SIZE};
-use piece::PiecePlugin;
+use bevy_mod_picking::DefaultPickingPlugins;
+use clap::{error::ErrorKind, CommandFactory, Parser};
 
 const WIDTH: f32 = 1024.0;
 const HEIGHT: f32 = 612.0;
 const BACKGROUND_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
 
-#[derive(Resource)]
-pub struct GameAssets {
-    white_pawn: Handle<Image>,
-    white_rock: Handle<Image>,
-    white_bishop: Handle<Image>,
-    white_knight: Handle<Image>,
-    white_queen: Handle<Image>,
-    white_king: Handle<Image>,
-    black_pawn: Handle<Image>,
-    black_rock: Handle<Image>,
-    black_knight: Handle<Image>,
-    black_bishop: Handle<Image>,
-    black_queen: Handle<Image>,
-    black_king: Handle<Image>,
-}
-
 fn main() {
-    App::new()
-        .insert_resource(ClearColor(BACKGROUND_COLOR))
+    let args = Args::parse();
+    // a borderless window that only shows the board on a see-through background, to be captured
+    // by streaming software
+    let overlay = args.overlay;
+    let mut app = App::new();
+
+    // the file logger has to be in place before any other plugin logs something
+    let mut default_plugins = DefaultPlugins.build();
+    if let Some(path) = args.log_file {
+        match FileLogPlugin::new(path.clone()) {
+            Ok(plugin) => app.add_plugin(plugin),
+            Err(e) => Args::command()
+                .error(
+                    ErrorKind::InvalidValue,
+                    format!("can't create {}: {}", path.display(), e),
+                )
+                .exit(),
+        };
+        default_plugins = default_plugins.disable::<LogPlugin>();
+    }
+
+    let background = match overlay {
+        true => Color::NONE,
+        false => BACKGROUND_COLOR,
+    };
+
+    app.insert_resource(ClearColor(background))
         .add_plugins(
-            DefaultPlugins
+            default_plugins
                 .set(WindowPlugin {
                     window: WindowDescriptor {
                         title: format!(
@@ -52,46 +50,49 @@ fn main() {
                         height: HEIGHT,
                         position: WindowPosition::Centered,
                         resizable: true,
+                        transparent: overlay,
+                        decorations: !overlay,
                         ..default()
                     },
                     ..default()
                 })
+                .set(AssetPlugin {
+                    // edited piece and tile images update the running game, as the entities
+                    // keep pointing to the same handles
+                    watch_for_changes: cfg!(not(target_arch = "wasm32")),
+                    ..default()
+                })
                 .set(ImagePlugin::default_nearest()),
         )
-        .add_plugin(WorldInspectorPlugin::new())
         .add_plugin(TilemapPlugin)
-        .add_plugins(DefaultPickingPlugins)
-        // Systems
-        .add_startup_system(spawn_camera)
-        .add_startup_system_to_stage(StartupStage::PreStartup, asset_loader)
-        .add_plugin(BoardPlugin)
-        .add_plugin(PiecePlugin)
-        .run();
-}
+        .add_plugins(DefaultPickingPlugins);
 
-fn spawn_camera(mut commands: Commands) {
-    commands.spawn((
-        Camera2dBundle {
-            transform: Transform::from_xyz(TILE_SIZE * 4.0, TILE_SIZE * 4.0, 999.9),
-            ..default()
-        },
-        PickingCameraBundle::default(),
-    ));
-}
+    // the position has to be known before the board is set up
+    if let Some(fen) = args.fen {
+        app.insert_resource(StartingPosition(fen));
+    }
+    if let Some(path) = args.pgn {
+        match Playback::load(&path) {
+            Ok(playback) => app.insert_resource(playback),
+            Err(e) => Args::command()
+                .error(
+                    ErrorKind::InvalidValue,
+                    format!("can't open {}: {}", path.display(), e),
+                )
+                .exit(),
+        };
+    }
+    if overlay {
+        app.insert_resource(Overlay);
+    }
+    app.add_plugin(BevyChessPlugin);
+
+    if !overlay {
+        app.add_plugin(WorldInspectorPlugin::new());
+    }
+    if let Some(path) = args.overlay_text {
+        app.add_plugin(OverlayTextPlugin { path });
+    }
 
-fn asset_loader(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.insert_resource(GameAssets {
-        white_pawn: asset_server.load("white_pawn.png"),
-        white_rock: asset_server.load("white_rock.png"),
-        white_knight: asset_server.load("white_knight.png"),
-        white_bishop: asset_server.load("white_bishop.png"),
-        white_queen: asset_server.load("white_queen.png"),
-        white_king: asset_server.load("white_king.png"),
-        black_pawn: asset_server.load("black_pawn.png"),
-        black_rock: asset_server.load("black_rock.png"),
-        black_knight: asset_server.load("black_knight.png"),
-        black_bishop: asset_server.load("black_bishop.png"),
-        black_queen: asset_server.load("black_queen.png"),
-        black_king: asset_server.load("black_king.png"),
-    });
+    app.run();
 }
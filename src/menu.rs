@@ -0,0 +1,110 @@
+use bevy::{app::AppExit, prelude::*};
+use bevy_egui::{egui, EguiContext};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    game::{GameState, NewGame},
+    menus_shown,
+    save::{LastGame, LoadGame},
+};
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            Self::main_menu
+                .run_in_state(GameState::MainMenu)
+                .run_if(menus_shown),
+        )
+        .add_system(Self::toggle_pause)
+        .add_system(
+            Self::pause_menu
+                .run_in_state(GameState::Paused)
+                .run_if(menus_shown),
+        );
+    }
+}
+
+impl MenuPlugin {
+    // shown when the game is launched, the board is behind it ready to be played on
+    fn main_menu(
+        mut commands: Commands,
+        mut egui_context: ResMut<EguiContext>,
+        last_game: Option<Res<LastGame>>,
+        mut new_game: EventWriter<NewGame>,
+        mut load: EventWriter<LoadGame>,
+        mut exit: EventWriter<AppExit>,
+    ) {
+        egui::Window::new("Chess")
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .collapsible(false)
+            .resizable(false)
+            .show(egui_context.ctx_mut(), |ui| {
+                ui.vertical_centered_justified(|ui| {
+                    if let Some(last_game) = &last_game {
+                        if ui.button("Resume last game").clicked() {
+                            load.send(LoadGame(last_game.0.clone()));
+                        }
+                    }
+                    if ui.button("Play").clicked() {
+                        commands.insert_resource(NextState(GameState::Playing));
+                    }
+                    if ui.button("New game").clicked() {
+                        new_game.send(NewGame);
+                    }
+                    if ui.button("Quit").clicked() {
+                        exit.send(AppExit);
+                    }
+                });
+            });
+    }
+
+    // pauses the game played when escape is pressed, and resumes it when it's pressed again
+    fn toggle_pause(
+        mut commands: Commands,
+        keys: Res<Input<KeyCode>>,
+        state: Res<CurrentState<GameState>>,
+    ) {
+        if !keys.just_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        match state.0 {
+            GameState::Playing => commands.insert_resource(NextState(GameState::Paused)),
+            GameState::Paused => commands.insert_resource(NextState(GameState::Playing)),
+            _ => {}
+        }
+    }
+
+    // dims the board, which can't be picked while paused, behind the pause options
+    fn pause_menu(
+        mut commands: Commands,
+        mut egui_context: ResMut<EguiContext>,
+        mut new_game: EventWriter<NewGame>,
+        mut exit: EventWriter<AppExit>,
+    ) {
+        egui::CentralPanel::default()
+            .frame(egui::Frame::none().fill(egui::Color32::from_black_alpha(160)))
+            .show(egui_context.ctx_mut(), |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(ui.available_height() / 3.0);
+                    ui.heading("Paused");
+                    ui.add_space(8.0);
+
+                    if ui.button("Resume").clicked() {
+                        commands.insert_resource(NextState(GameState::Playing));
+                    }
+                    if ui.button("New game").clicked() {
+                        new_game.send(NewGame);
+                    }
+                    if ui.button("Main menu").clicked() {
+                        commands.insert_resource(NextState(GameState::MainMenu));
+                    }
+                    if ui.button("Quit").clicked() {
+                        exit.send(AppExit);
+                    }
+                });
+            });
+    }
+}
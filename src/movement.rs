@@ -1,205 +1,456 @@
+use std::marker::PhantomData;
+
 use bevy::{
+    ecs::system::SystemParam,
     prelude::{
-        info, Assets, Changed, Commands, Entity, EventReader, EventWriter, Mesh, Query, ResMut,
-        Transform, Vec2, Vec3, With,
+        debug, info, info_span, Changed, Commands, Entity, EventReader, EventWriter, Handle, Image,
+        Query, Res, ResMut, Resource, Transform, Vec2, Vec3, With,
     },
-    sprite::ColorMaterial,
-};
-use bevy_ecs_tilemap::{
-    prelude::{TilemapGridSize, TilemapSize, TilemapType},
-    tiles::{TilePos, TileStorage},
 };
+use bevy_ecs_tilemap::tiles::TilePos;
 use bevy_mod_picking::{PickingEvent, Selection, SelectionEvent};
+use chess_core::{legal_moves, to_san, Board, Move, Piece, PieceKind, Square, Team, Undo};
+use iyes_loopless::state::NextState;
 
 use crate::{
-    board::{Tile, TileState},
-    piece::{HighLight, PieceDeathEvent, PieceType},
+    board::{
+        board_tilemap, to_square, to_tile_pos, BoardId, BoardTiles, ChessBoard, Tile, TileState,
+        TilemapQuery,
+    },
+    game::GameState,
+    piece::{Highlighter, PieceDeathEvent, PieceType, SelectedPiece, TouchedPiece, TurnState},
+    promotion::PendingPromotion,
+    settings::Settings,
+    GameAssets,
 };
 
-pub struct MoveEvent;
+// a move a player asked for, it's only made if the rules allow it
+#[derive(Clone, Copy, Debug)]
+pub struct MoveRequested(pub Move);
+
+// sent once a move is made on the board
+#[derive(Clone, Debug)]
+pub struct MoveMade {
+    pub mv: Move,
+    pub team: Team,
+    pub captured: Option<Piece>,
+    // the move in standard algebraic notation, written once here for everything that shows it
+    pub san: String,
+    // what's needed to take the move back
+    pub undo: Undo,
+}
+
+// the moves the team to move can make, worked out once per position and asked by both the
+// highlights and move_piece, so what's shown and what's accepted never disagree
+#[derive(Resource, Default)]
+pub struct MoveValidator {
+    moves: Vec<Move>,
+}
+
+impl MoveValidator {
+    pub fn new(board: &Board, team: Team) -> Self {
+        let mut moves = Vec::new();
+
+        for (square, piece) in board.pieces() {
+            if piece.team != team {
+                continue;
+            }
+
+            let piece_moves = legal_moves(board, square);
+            #[cfg(all(debug_assertions, feature = "validate-moves"))]
+            chess_core::validation::validate_legal_moves(board, square, &piece_moves);
+            moves.extend(piece_moves);
+        }
+        debug!(count = moves.len(), "legal moves generated");
+
+        Self { moves }
+    }
+
+    // the moves of the piece on the square, none if it isn't a piece of the team to move
+    pub fn legal_moves(&self, square: Square) -> Vec<Move> {
+        self.moves
+            .iter()
+            .filter(|mv| mv.from == square)
+            .copied()
+            .collect()
+    }
+
+    pub fn is_legal(&self, mv: Move) -> bool {
+        self.moves.contains(&mv)
+    }
+
+    // whether the move is legal once a piece is picked for the pawn reaching the last rank
+    pub fn is_promotion(&self, mv: Move) -> bool {
+        self.moves
+            .iter()
+            .any(|legal| legal.from == mv.from && legal.to == mv.to && legal.promotion.is_some())
+    }
+}
+
+// works the legal moves out again whenever the position or the team to move change
+pub fn update_move_validator(
+    board: Res<ChessBoard>,
+    turn: Res<TurnState>,
+    mut validator: ResMut<MoveValidator>,
+) {
+    if board.is_changed() || turn.is_changed() {
+        *validator = MoveValidator::new(&board.0, turn.0);
+    }
+}
+
+// the pieces that can be picked up, and the one picked up
+#[derive(SystemParam)]
+pub struct PickedPieces<'w, 's> {
+    piece_q: Query<'w, 's, (&'static PieceType, &'static BoardId, &'static Transform)>,
+    selection_q: Query<'w, 's, &'static mut Selection, With<PieceType>>,
+    selected: ResMut<'w, SelectedPiece>,
+    touched: ResMut<'w, TouchedPiece>,
+}
 
-// detects wether a piece has been selected and shows, with a circle, where the player can move
-// the piece to, depending on it's type
+// detects wether a piece of the team to move has been selected and shows, with a circle, where
+// the player can move the piece to, as the rules tell. with touch-move on, a piece picked up that
+// can move stays picked up until it's moved
 pub fn get_piece_movements(
-    mut commands: Commands,
     mut events: EventReader<PickingEvent>,
-    mut tile_state_q: Query<&mut TileState>,
-    piece_type: Query<&PieceType>,
-    tile_storage_q: Query<(&TileStorage, &TilemapGridSize, &TilemapSize, &TilemapType)>,
-    transform_q: Query<&mut Transform>,
-    highlight_pos: Query<Entity, With<HighLight>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut tiles: BoardTiles,
+    mut pieces: PickedPieces,
+    validator: Res<MoveValidator>,
+    turn: Res<TurnState>,
+    settings: Res<Settings>,
+    mut highlighter: Highlighter,
 ) {
+    // only the pieces of the team to move, on the board the game is played on, can be moved
+    let mut picked = None;
+    let mut dropped = false;
     for event in events.iter() {
-        let (tile_storage, grid_size, map_size, map_type) = tile_storage_q.single();
-        if highlight_pos.is_empty() {
-            if let PickingEvent::Selection(e) = event {
-                if let SelectionEvent::JustSelected(s) = e {
-                    if let Ok(piece_t) = piece_type.get(*s) {
-                        //get the cursor position, if it is on the window
-                        if let Ok(t) = transform_q.get(*s) {
-                            let pos = Vec2::new(t.translation.x, t.translation.y);
-                            // gets the position of tile selected by the player
-                            let tile_pos =
-                                TilePos::from_world_pos(&pos, map_size, grid_size, map_type)
-                                    .unwrap();
-                            match piece_t {
-                                PieceType::Rock(r) => r.movement(
-                                    &mut commands,
-                                    tile_storage,
-                                    grid_size,
-                                    map_size,
-                                    map_type,
-                                    &mut tile_state_q,
-                                    &piece_type,
-                                    tile_pos,
-                                    &mut meshes,
-                                    &mut materials,
-                                ),
-                                PieceType::Knight(kn) => kn.knight_movement(
-                                    &mut commands,
-                                    tile_storage,
-                                    tile_pos,
-                                    &mut tile_state_q,
-                                    &piece_type,
-                                    grid_size,
-                                    map_type,
-                                    &mut meshes,
-                                    &mut materials,
-                                ),
-                                PieceType::Bishop(b) => b.movement(
-                                    &mut commands,
-                                    tile_storage,
-                                    grid_size,
-                                    map_size,
-                                    map_type,
-                                    &mut tile_state_q,
-                                    &piece_type,
-                                    tile_pos,
-                                    &mut meshes,
-                                    &mut materials,
-                                ),
-                                PieceType::Queen(q) => q.movement(
-                                    &mut commands,
-                                    tile_storage,
-                                    grid_size,
-                                    map_size,
-                                    map_type,
-                                    &mut tile_state_q,
-                                    &piece_type,
-                                    tile_pos,
-                                    &mut meshes,
-                                    &mut materials,
-                                ),
-                                PieceType::King(k) => k.movement(
-                                    &mut commands,
-                                    tile_storage,
-                                    tile_pos,
-                                    &mut tile_state_q,
-                                    &piece_type,
-                                    grid_size,
-                                    map_size,
-                                    map_type,
-                                    &mut meshes,
-                                    &mut materials,
-                                ),
-                                PieceType::Pawn(p) => p.movement(
-                                    &mut commands,
-                                    tile_pos,
-                                    tile_storage,
-                                    &mut tile_state_q,
-                                    &piece_type,
-                                    grid_size,
-                                    map_size,
-                                    map_type,
-                                    &mut meshes,
-                                    &mut materials,
-                                ),
-                            }
-                        }
+        match event {
+            PickingEvent::Selection(SelectionEvent::JustSelected(s)) => {
+                if let Ok((piece_t, board_id, _)) = pieces.piece_q.get(*s) {
+                    if piece_t.get_team() == turn.0 && *board_id == BoardId::MAIN {
+                        picked = Some(*s);
                     }
                 }
             }
+            PickingEvent::Selection(SelectionEvent::JustDeselected(s)) => {
+                dropped |= pieces.touched.0 == Some(*s);
+            }
+            _ => {}
+        }
+    }
+
+    // the piece touched is picked up again whatever else was clicked, as it has to be moved
+    if let Some(touched_piece) = pieces.touched.0.filter(|_| settings.touch_move) {
+        if picked != Some(touched_piece) && (picked.is_some() || dropped) {
+            if let Some(Ok(mut selection)) = picked.map(|s| pieces.selection_q.get_mut(s)) {
+                selection.set_selected(false);
+            }
+            if let Ok(mut selection) = pieces.selection_q.get_mut(touched_piece) {
+                selection.set_selected(true);
+            }
+            picked = Some(touched_piece);
+        }
+    }
+
+    let s = match picked {
+        Some(s) => s,
+        None => return,
+    };
+    let (tile_storage, grid_size, map_size, map_type) =
+        match board_tilemap(&tiles.tilemap_q, BoardId::MAIN) {
+            Some(tilemap) => tilemap,
+            None => return,
+        };
+
+    //get the cursor position, if it is on the window
+    if let Ok((_, _, t)) = pieces.piece_q.get(s) {
+        let pos = Vec2::new(t.translation.x, t.translation.y);
+        // gets the position of tile selected by the player
+        let tile_pos = TilePos::from_world_pos(&pos, map_size, grid_size, map_type).unwrap();
+
+        // a piece captured this same frame is still selectable, but no longer owns its tile
+        let tile_s = tiles
+            .tile_state_q
+            .get(tile_storage.get(&tile_pos).unwrap())
+            .unwrap();
+        if tile_s.piece_ent != Some(s) {
+            return;
+        }
+
+        let _span = info_span!("piece_movements", square = ?to_square(tile_pos)).entered();
+        let moves = validator.legal_moves(to_square(tile_pos));
+        pieces.selected.0 = Some(s);
+        if settings.touch_move && !moves.is_empty() {
+            pieces.touched.0 = Some(s);
+        }
+
+        for mv in moves {
+            let target = to_tile_pos(mv.to);
+            let mut target_s = tiles
+                .tile_state_q
+                .get_mut(tile_storage.get(&target).unwrap())
+                .unwrap();
+
+            // every promotion goes to the same tile, which is highlighted once
+            if let Tile::HighLighted = target_s.tile_type {
+                continue;
+            }
+            target_s.tile_type = Tile::HighLighted;
+            highlighter.highlight_tile(BoardId::MAIN, grid_size, map_type, &target);
         }
     }
 }
 
-pub fn move_piece(
-    mut _commands: Commands,
+// the circle picked to move to, with what's needed to find the tile it's on
+#[derive(SystemParam)]
+pub struct PickedCircles<'w, 's> {
+    tile_state_q: Query<'w, 's, &'static TileState>,
+    transform_q: Query<'w, 's, &'static Transform>,
+    tile_storage_q: TilemapQuery<'w, 's>,
+    board_id_q: Query<'w, 's, &'static BoardId>,
+    selected_pos: Query<'w, 's, (Entity, &'static Selection), Changed<Selection>>,
+}
+
+// asks for the move of the piece just deselected to the circle just selected, once a piece is
+// picked if it's a pawn reaching the last rank
+pub fn request_move(
+    mut commands: Commands,
     mut events: EventReader<PickingEvent>,
-    mut tile_state_q: Query<&mut TileState>,
-    mut transform_q: Query<&mut Transform>,
-    tile_storage_q: Query<(&TileStorage, &TilemapGridSize, &TilemapSize, &TilemapType)>,
-    selected_pos: Query<Entity, Changed<Selection>>,
-    mut move_event: EventWriter<MoveEvent>,
-    mut death_event: EventWriter<PieceDeathEvent>,
+    circles: PickedCircles,
+    validator: Res<MoveValidator>,
+    selected: Res<SelectedPiece>,
+    settings: Res<Settings>,
+    mut move_request: EventWriter<MoveRequested>,
 ) {
     for event in events.iter() {
-        if let PickingEvent::Selection(e) = event {
-            if let SelectionEvent::JustDeselected(s) = e {
-                let (tile_storage, grid_size, map_size, map_type) = tile_storage_q.single();
-
-                //get the entity of the selected circle
-                for selection in selected_pos.iter() {
-                    //get the transform of the selected circle
-                    if let Ok(transform_s) = transform_q.get(selection) {
-                        // convert the transform into a 2d vec
-                        let pos = Vec2::new(transform_s.translation.x, transform_s.translation.y);
-                        // get the position of tile selected by the player
-                        let tile_pos =
-                            TilePos::from_world_pos(&pos, map_size, grid_size, map_type).unwrap();
-                        info!("{:?}", tile_pos);
-
-                        // checks wether the movement is correct
-                        if let Tile::HighLighted = tile_state_q
-                            .get_mut(tile_storage.get(&tile_pos).unwrap())
-                            .unwrap()
-                            .tile_type
-                        {
-                            // gets the reference to the selection's transform to be changed
-                            let mut selection_t = transform_q.get_mut(*s).unwrap();
-                            // converts the tile position into the transform which is at the
-                            // center of the selected tile
-                            let new_pos = tile_pos.center_in_world(grid_size, map_type);
-                            // get the old tile position
-                            let old_tile = TilePos::from_world_pos(
-                                &Vec2::new(selection_t.translation.x, selection_t.translation.y),
-                                map_size,
-                                grid_size,
-                                map_type,
-                            )
-                            .unwrap();
-
-                            //get the old tile state and change its type to empty
-                            let mut tile_s = tile_state_q
-                                .get_mut(tile_storage.get(&old_tile).unwrap())
-                                .unwrap();
-                            let piece = tile_s.piece_ent.unwrap();
-
-                            tile_s.tile_type = Tile::Empty;
-                            tile_s.piece_ent = None;
-
-                            //get the selected tile state and change its type to empty
-                            tile_s = tile_state_q
-                                .get_mut(tile_storage.get(&tile_pos).unwrap())
-                                .unwrap();
-
-                            // if theres some piece on the tile just selected, send a death event
-                            if let Some(e) = tile_s.piece_ent {
-                                death_event.send(PieceDeathEvent(e));
-                            }
+        if let PickingEvent::Selection(SelectionEvent::JustDeselected(s)) = event {
+            // the circles shown are only good for the piece they were made for
+            if selected.0 != Some(*s) {
+                continue;
+            }
+            let board_id = match circles.board_id_q.get(*s) {
+                Ok(board_id) if *board_id == BoardId::MAIN => *board_id,
+                _ => continue,
+            };
+            let (tile_storage, grid_size, map_size, map_type) =
+                match board_tilemap(&circles.tile_storage_q, board_id) {
+                    Some(tilemap) => tilemap,
+                    None => continue,
+                };
 
-                            tile_s.tile_type = Tile::NotEmpty;
-                            tile_s.piece_ent = Some(piece);
+            //get the entity of the selected circle
+            for (selection, selection_s) in circles.selected_pos.iter() {
+                // circles going back to the pool are deselected too
+                if !selection_s.selected() {
+                    continue;
+                }
+                // a circle on another board can't be where this piece goes
+                if circles.board_id_q.get(selection) != Ok(&board_id) {
+                    continue;
+                }
+
+                //get the transform of the selected circle
+                if let (Ok(transform_s), Ok(piece_t)) = (
+                    circles.transform_q.get(selection),
+                    circles.transform_q.get(*s),
+                ) {
+                    // convert the transform into a 2d vec
+                    let pos = Vec2::new(transform_s.translation.x, transform_s.translation.y);
+                    // get the position of tile selected by the player
+                    let tile_pos =
+                        TilePos::from_world_pos(&pos, map_size, grid_size, map_type).unwrap();
 
-                            selection_t.translation = Vec3::new(new_pos.x, new_pos.y, 1.0);
+                    // checks wether the circle selected was offered to the player
+                    if let Tile::HighLighted = circles
+                        .tile_state_q
+                        .get(tile_storage.get(&tile_pos).unwrap())
+                        .unwrap()
+                        .tile_type
+                    {
+                        // get the old tile position
+                        let old_tile = TilePos::from_world_pos(
+                            &Vec2::new(piece_t.translation.x, piece_t.translation.y),
+                            map_size,
+                            grid_size,
+                            map_type,
+                        )
+                        .unwrap();
+                        let mv = Move::new(to_square(old_tile), to_square(tile_pos));
+
+                        // the board waits for the piece the pawn turns into to be picked,
+                        // unless it's always a queen
+                        match (validator.is_promotion(mv), settings.auto_queen) {
+                            (true, true) => move_request.send(MoveRequested(Move {
+                                promotion: Some(PieceKind::Queen),
+                                ..mv
+                            })),
+                            (true, false) => {
+                                commands.insert_resource(PendingPromotion(mv));
+                                commands.insert_resource(NextState(GameState::Promotion));
+                            }
+                            (false, _) => move_request.send(MoveRequested(mv)),
                         }
                     }
                 }
+            }
+        }
+    }
+}
+
+// the sprites of the pieces, moved along with the moves and drawn again when a pawn is promoted
+#[derive(SystemParam)]
+pub struct PieceSprites<'w, 's> {
+    transform_q: Query<'w, 's, &'static mut Transform>,
+    piece_q: Query<'w, 's, (&'static mut PieceType, &'static mut Handle<Image>)>,
+    game_assets: Res<'w, GameAssets>,
+}
+
+// the position the game is played on, the team to move and the moves it can make
+#[derive(SystemParam)]
+pub struct PlayedPosition<'w, 's> {
+    board: ResMut<'w, ChessBoard>,
+    turn: ResMut<'w, TurnState>,
+    validator: ResMut<'w, MoveValidator>,
+    #[system_param(ignore)]
+    _marker: PhantomData<&'s ()>,
+}
+
+// makes the moves asked for on the board the game is played on, if the rules allow them, and
+// moves the sprites along
+pub fn move_piece(
+    mut move_request: EventReader<MoveRequested>,
+    mut tiles: BoardTiles,
+    mut sprites: PieceSprites,
+    mut position: PlayedPosition,
+    mut move_made: EventWriter<MoveMade>,
+    mut death_event: EventWriter<PieceDeathEvent>,
+) {
+    let (tile_storage, grid_size, _, map_type) =
+        match board_tilemap(&tiles.tilemap_q, BoardId::MAIN) {
+            Some(tilemap) => tilemap,
+            None => return,
+        };
+
+    for MoveRequested(mv) in move_request.iter().copied() {
+        let _span = info_span!("move_piece", from = ?mv.from, to = ?mv.to).entered();
+        // the rules have the last word on wether the move can be made, only the moves of the
+        // team to move are legal
+        if !position.validator.is_legal(mv) {
+            debug!("illegal move rejected");
+            continue;
+        }
+        #[cfg(all(debug_assertions, feature = "validate-moves"))]
+        let before = position.board.0.clone();
+        let captured_square = position.board.0.captured_square(mv);
+        let san = to_san(&position.board.0, mv);
+        let undo = position
+            .board
+            .0
+            .undo_for(mv)
+            .expect("a legal move has a piece to move");
+        let captured = position.board.0.make_move(mv);
+        #[cfg(all(debug_assertions, feature = "validate-moves"))]
+        chess_core::validation::validate_move(&before, mv, &position.board.0);
+
+        let (old_tile, tile_pos) = (to_tile_pos(mv.from), to_tile_pos(mv.to));
+
+        //get the old tile state and change its type to empty
+        let mut tile_s = tiles
+            .tile_state_q
+            .get_mut(tile_storage.get(&old_tile).unwrap())
+            .unwrap();
+        let piece = tile_s.piece_ent.unwrap();
+
+        tile_s.tile_type = Tile::Empty;
+        tile_s.piece_ent = None;
+
+        //get the selected tile state and change its type to empty
+        tile_s = tiles
+            .tile_state_q
+            .get_mut(tile_storage.get(&tile_pos).unwrap())
+            .unwrap();
+
+        // if theres some piece on the tile just selected, send a death event
+        if let Some(e) = tile_s.piece_ent {
+            death_event.send(PieceDeathEvent(e));
+        }
+
+        tile_s.tile_type = Tile::NotEmpty;
+        tile_s.piece_ent = Some(piece);
+
+        // a pawn taken en passant isn't on the tile just selected, so it's removed from its own
+        // tile
+        if captured_square != mv.to {
+            tile_s = tiles
+                .tile_state_q
+                .get_mut(tile_storage.get(&to_tile_pos(captured_square)).unwrap())
+                .unwrap();
+
+            if let Some(e) = tile_s.piece_ent {
+                death_event.send(PieceDeathEvent(e));
+            }
+
+            tile_s.tile_type = Tile::Empty;
+            tile_s.piece_ent = None;
+        }
+
+        // converts the tile position into the transform which is at the center of the tile
+        let new_pos = tile_pos.center_in_world(grid_size, map_type);
+        if let Ok(mut piece_t) = sprites.transform_q.get_mut(piece) {
+            piece_t.translation = Vec3::new(new_pos.x, new_pos.y, 1.0);
+        }
+
+        // a castling king takes its rook along, to the square it jumped over
+        if let Some(rook) = mv.castling_rook(undo.piece) {
+            let (rook_old, rook_new) = (to_tile_pos(rook.from), to_tile_pos(rook.to));
+
+            tile_s = tiles
+                .tile_state_q
+                .get_mut(tile_storage.get(&rook_old).unwrap())
+                .unwrap();
+            let rook_piece = tile_s.piece_ent.take();
+            tile_s.tile_type = Tile::Empty;
+
+            tile_s = tiles
+                .tile_state_q
+                .get_mut(tile_storage.get(&rook_new).unwrap())
+                .unwrap();
+            tile_s.tile_type = Tile::NotEmpty;
+            tile_s.piece_ent = rook_piece;
 
-                move_event.send(MoveEvent)
+            let rook_pos = rook_new.center_in_world(grid_size, map_type);
+            if let Some(Ok(mut rook_t)) = rook_piece.map(|e| sprites.transform_q.get_mut(e)) {
+                rook_t.translation = Vec3::new(rook_pos.x, rook_pos.y, 1.0);
             }
         }
+
+        // a promoted pawn becomes the piece picked, and is drawn like it
+        if let (Some(kind), Ok((mut piece_type, mut image))) =
+            (mv.promotion, sprites.piece_q.get_mut(piece))
+        {
+            let promoted = Piece::new(kind, position.turn.0);
+
+            *piece_type = PieceType::new(promoted);
+            *image = sprites.game_assets.piece_image(promoted);
+        }
+
+        info!(
+            team = ?position.turn.0,
+            san = %san,
+            captured = ?captured.map(|p| p.kind),
+            promotion = ?mv.promotion,
+            "move applied"
+        );
+
+        move_made.send(MoveMade {
+            mv,
+            team: position.turn.0,
+            captured,
+            san,
+            undo,
+        });
+
+        // the move is done, it's the other team's turn
+        position.turn.0 = position.turn.0.opposite();
+        *position.validator = MoveValidator::new(&position.board.0, position.turn.0);
     }
 }
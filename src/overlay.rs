@@ -0,0 +1,43 @@
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+
+use crate::{board::ChessBoard, piece::TurnState};
+
+// inserted before the game's plugins when the window is a see-through overlay for streaming
+// software: the game starts right away and the menus aren't drawn over the board
+#[derive(Resource)]
+pub struct Overlay;
+
+// writes the side to move and the FEN of the position to a text file every time they change, for
+// streaming software to show it as a text source
+pub struct OverlayTextPlugin {
+    pub path: PathBuf,
+}
+
+#[derive(Resource)]
+struct OverlayText(PathBuf);
+
+impl Plugin for OverlayTextPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(OverlayText(self.path.clone()))
+            .add_system(Self::write_overlay_text);
+    }
+}
+
+impl OverlayTextPlugin {
+    fn write_overlay_text(
+        overlay_text: Res<OverlayText>,
+        board: Res<ChessBoard>,
+        turn: Res<TurnState>,
+    ) {
+        if !board.is_changed() && !turn.is_changed() {
+            return;
+        }
+
+        let text = format!("{:?} to move\n{}", turn.0, board.0.to_fen(turn.0));
+        if let Err(e) = fs::write(&overlay_text.0, text) {
+            error!("Couldn't write {}: {}", overlay_text.0.display(), e);
+        }
+    }
+}
@@ -1,37 +1,100 @@
 use bevy::{
+    ecs::system::SystemParam,
     prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
     sprite::{MaterialMesh2dBundle, Mesh2dHandle},
 };
 use bevy_ecs_tilemap::{
-    prelude::{TilemapGridSize, TilemapSize, TilemapType},
-    tiles::{TilePos, TileStorage},
+    prelude::{TilemapGridSize, TilemapType},
+    tiles::TilePos,
 };
-use bevy_mod_picking::{PickableBundle, PickingEvent};
+use bevy_mod_picking::{Hover, PickableBundle, PickingEvent, Selection};
+use chess_core::{Piece, PieceKind, Team};
 use iyes_loopless::prelude::*;
 
 use crate::{
     bishop::Bishop,
-    board::{Tile, TileState},
+    board::{BoardId, PositionReset, Tile, TileState, TILE_SIZE},
+    game::GameState,
     king::King,
     knight::Knight,
-    movement::{get_piece_movements, move_piece, MoveEvent},
+    movement::{
+        get_piece_movements, move_piece, request_move, update_move_validator, MoveMade,
+        MoveRequested, MoveValidator,
+    },
     pawn::Pawn,
     queen::Queen,
-    rock::Rock,
+    rook::Rook,
+    settings::{Indicator, IndicatorShape, Settings},
 };
 
-#[derive(PartialEq, Eq, Clone, Copy)]
-pub enum Team {
-    White,
-    Black,
+// the team whose pieces can be selected and moved
+#[derive(Resource)]
+pub struct TurnState(pub Team);
+
+impl Default for TurnState {
+    fn default() -> Self {
+        Self(Team::White)
+    }
 }
+
 #[derive(Component)]
 pub struct HighLight;
 
-#[derive(Component, Clone, Copy)]
+// the material every highlight is drawn with, shared so the theme can recolor all of them at once
+#[derive(Resource)]
+pub struct HighlightMaterial(pub Handle<ColorMaterial>);
+
+// what the highlights are made of: the indicator is only drawn, and it's a see-through quad as big
+// as a tile that is clicked, so the square can be picked anywhere whatever the indicator looks like
+#[derive(Resource)]
+pub struct HighlightAssets {
+    // replaced when a theme with another indicator is picked
+    pub indicator: Handle<Mesh>,
+    pub tile: Handle<Mesh>,
+    pub tile_material: Handle<ColorMaterial>,
+}
+
+impl FromWorld for HighlightAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let indicator = meshes.add(indicator_mesh(&Indicator::default(), false));
+        let tile = meshes.add(Mesh::from(shape::Quad::new(Vec2::splat(TILE_SIZE))));
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+
+        Self {
+            indicator,
+            tile,
+            tile_material: materials.add(ColorMaterial::from(Color::NONE)),
+        }
+    }
+}
+
+// the piece the highlights shown were made for, a move is only taken from this piece
+#[derive(Resource, Default)]
+pub struct SelectedPiece(pub Option<Entity>);
+
+// with touch-move on, the piece picked up that has to be moved, until a move is made
+#[derive(Resource, Default)]
+pub struct TouchedPiece(pub Option<Entity>);
+
+// highlights hidden once their selection ended, shown again for the next ones instead of spawning
+// new entities and meshes on every click
+#[derive(Resource, Default)]
+pub struct HighlightPool(Vec<Entity>);
+
+impl FromWorld for HighlightMaterial {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+
+        Self(materials.add(ColorMaterial::from(Color::hex("3181C6").unwrap())))
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
 pub enum PieceType {
     Pawn(Pawn),
-    Rock(Rock),
+    Rook(Rook),
     Bishop(Bishop),
     Knight(Knight),
     Queen(Queen),
@@ -39,95 +102,328 @@ pub enum PieceType {
 }
 
 impl PieceType {
+    pub fn new(piece: Piece) -> Self {
+        let team = piece.team;
+
+        match piece.kind {
+            PieceKind::Pawn => Self::Pawn(Pawn { team }),
+            PieceKind::Rook => Self::Rook(Rook { team }),
+            PieceKind::Knight => Self::Knight(Knight { team }),
+            PieceKind::Bishop => Self::Bishop(Bishop { team }),
+            PieceKind::Queen => Self::Queen(Queen { team }),
+            PieceKind::King => Self::King(King { team }),
+        }
+    }
+
     pub fn get_team(self) -> Team {
         match self {
             Self::Pawn(p) => p.team,
-            Self::Rock(r) => r.team,
+            Self::Rook(r) => r.team,
             Self::Knight(kn) => kn.team,
             Self::Bishop(b) => b.team,
             Self::Queen(q) => q.team,
             Self::King(k) => k.team,
         }
     }
+
+    pub fn piece(self) -> Piece {
+        let kind = match self {
+            Self::Pawn(_) => PieceKind::Pawn,
+            Self::Rook(_) => PieceKind::Rook,
+            Self::Knight(_) => PieceKind::Knight,
+            Self::Bishop(_) => PieceKind::Bishop,
+            Self::Queen(_) => PieceKind::Queen,
+            Self::King(_) => PieceKind::King,
+        };
+
+        Piece::new(kind, self.get_team())
+    }
 }
 
+// sent with the entity of a captured piece, which is despawned
 pub struct PieceDeathEvent(pub Entity);
 
+// sent once a captured piece has been taken off the board, for the parts of the game showing
+// captures
+#[derive(Clone, Copy, Debug)]
+pub struct PieceCaptured(pub Piece);
+
 pub struct PiecePlugin;
 
+// lets other plugins order their systems around a move being made
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PieceSystem {
+    MovePiece,
+    ClearHighlights,
+}
+
 impl Plugin for PiecePlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(get_piece_movements.run_on_event::<PickingEvent>())
-            .add_system(move_piece.run_on_event::<PickingEvent>())
-            .add_system(reset_neighbors.run_on_event::<MoveEvent>())
-            .add_event::<MoveEvent>()
+        app.init_resource::<TurnState>()
+            .init_resource::<HighlightMaterial>()
+            .init_resource::<HighlightAssets>()
+            .init_resource::<HighlightPool>()
+            .init_resource::<SelectedPiece>()
+            .init_resource::<TouchedPiece>()
+            .init_resource::<MoveValidator>()
+            .add_event::<MoveRequested>()
+            .add_event::<MoveMade>()
             .add_event::<PieceDeathEvent>()
-            .add_system(handle_piece_death.run_on_event::<PieceDeathEvent>())
-            .run();
+            .add_event::<PieceCaptured>()
+            // a move has to be resolved before the highlights it relies on are cleared, and
+            // those have to be cleared before the next selection is highlighted
+            // the legal moves of a position set from outside a move are known before anything
+            // is picked
+            .add_system(
+                update_move_validator
+                    .before(request_move)
+                    .before(PieceSystem::ClearHighlights),
+            )
+            // moves are only asked for and made while the game is played, not while a menu is
+            // open, a promotion is picked or a finished game is looked at
+            .add_system(
+                request_move
+                    .run_in_state(GameState::Playing)
+                    .run_on_event::<PickingEvent>()
+                    .before(PieceSystem::MovePiece),
+            )
+            .add_system(
+                move_piece
+                    .run_in_state(GameState::Playing)
+                    .run_on_event::<MoveRequested>()
+                    .label(PieceSystem::MovePiece),
+            )
+            .add_system(
+                clear_highlights
+                    .label(PieceSystem::ClearHighlights)
+                    .after(PieceSystem::MovePiece),
+            )
+            .add_system(
+                get_piece_movements
+                    .run_in_state(GameState::Playing)
+                    .run_on_event::<PickingEvent>()
+                    .after(PieceSystem::ClearHighlights),
+            )
+            .add_system(
+                release_touched_piece
+                    .run_on_event::<MoveMade>()
+                    .after(PieceSystem::MovePiece)
+                    .before(PieceSystem::ClearHighlights),
+            )
+            .add_system(show_ghost.after(get_piece_movements))
+            .add_system(
+                update_cursor
+                    .run_on_event::<PickingEvent>()
+                    .after(PieceSystem::MovePiece),
+            )
+            .add_system(handle_piece_death.run_on_event::<PieceDeathEvent>());
     }
 }
 
-fn reset_neighbors(
-    mut commands: Commands,
-    mut tile_state: Query<&mut TileState>,
-    move_event: EventReader<MoveEvent>,
-    transform_q: Query<&mut Transform>,
-    tile_query: Query<(&TileStorage, &TilemapGridSize, &TilemapSize, &TilemapType)>,
-    highlight_pos: Query<Entity, With<HighLight>>,
+// every time a selection begins or ends, hides all the circles and sets back the state of the
+// tiles they were shown on, so no highlight outlives the selection it was made for. a new
+// position drops the selection, and the piece touched, as their pieces are gone
+fn clear_highlights(
+    mut events: EventReader<PickingEvent>,
+    mut resets: EventReader<PositionReset>,
+    mut tile_state_q: Query<&mut TileState>,
+    mut highlight_q: Query<(Entity, &mut Visibility, &mut Selection), With<HighLight>>,
+    mut pool: ResMut<HighlightPool>,
+    mut selected: ResMut<SelectedPiece>,
+    mut touched: ResMut<TouchedPiece>,
 ) {
-    let (tile_storage, grid_size, map_size, map_type) = tile_query.single();
-    for ent in highlight_pos.iter() {
-        let transform = transform_q.get(ent).unwrap();
-        let tile_pos = TilePos::from_world_pos(
-            &Vec2::new(transform.translation.x, transform.translation.y),
-            map_size,
-            grid_size,
-            map_type,
-        )
-        .unwrap();
-        let neighbor = tile_storage.get(&tile_pos).unwrap();
-        let mut neigh_state = tile_state.get_mut(neighbor).unwrap();
-
-        // avoid setting to Empty the state of the neighbor we have moved the piece to
-        if let Tile::HighLighted = neigh_state.tile_type {
-            match neigh_state.piece_ent {
-                Some(_e) => neigh_state.tile_type = Tile::NotEmpty,
-                None => neigh_state.tile_type = Tile::Empty,
+    let selection_changed = events
+        .iter()
+        .any(|event| matches!(event, PickingEvent::Selection(_)));
+    let reset = resets.iter().count() > 0;
+
+    if !selection_changed && !reset {
+        return;
+    }
+    selected.0 = None;
+    if reset {
+        touched.0 = None;
+    }
+
+    // hidden entities can't be hovered or clicked, but the circle that was clicked stays
+    // selected unless told otherwise
+    for (ent, mut visibility, mut selection) in highlight_q.iter_mut() {
+        if visibility.is_visible {
+            visibility.is_visible = false;
+            selection.set_selected(false);
+            pool.0.push(ent);
+        }
+    }
+
+    for mut tile_s in tile_state_q.iter_mut() {
+        if let Tile::HighLighted = tile_s.tile_type {
+            match tile_s.piece_ent {
+                Some(_e) => tile_s.tile_type = Tile::NotEmpty,
+                None => tile_s.tile_type = Tile::Empty,
             }
         }
+    }
+}
+
+// shows a hand while the cursor is over a piece that can be moved or a circle it can be moved to
+fn update_cursor(
+    mut windows: ResMut<Windows>,
+    hover_q: Query<(&Hover, Option<&PieceType>, Option<&HighLight>)>,
+    turn: Res<TurnState>,
+) {
+    let over_movable = hover_q.iter().any(|(hover, piece_t, highlight)| {
+        hover.hovered() && (highlight.is_some() || piece_t.is_some_and(|p| p.get_team() == turn.0))
+    });
+    let icon = match over_movable {
+        true => CursorIcon::Hand,
+        false => CursorIcon::Default,
+    };
+
+    if let Some(window) = windows.get_primary_mut() {
+        if window.cursor_icon() != icon {
+            window.set_cursor_icon(icon);
+        }
+    }
+}
+
+// what's needed to mark the squares a piece can move to, the highlights hidden in the pool being
+// shown again before new ones are spawned
+#[derive(SystemParam)]
+pub struct Highlighter<'w, 's> {
+    commands: Commands<'w, 's>,
+    pool: ResMut<'w, HighlightPool>,
+    assets: Res<'w, HighlightAssets>,
+    material: Res<'w, HighlightMaterial>,
+}
+
+impl Highlighter<'_, '_> {
+    pub fn highlight_tile(
+        &mut self,
+        board_id: BoardId,
+        grid_size: &TilemapGridSize,
+        map_type: &TilemapType,
+        tile_pos: &TilePos,
+    ) {
+        // 2D vector with the x and y of the tile transform
+        let vec_t = tile_pos.center_in_world(grid_size, map_type);
+        let transform = Transform::from_xyz(vec_t.x, vec_t.y, 0.1);
 
-        commands.entity(ent).despawn_recursive();
+        if let Some(ent) = self.pool.0.pop() {
+            self.commands
+                .entity(ent)
+                .insert((transform, Visibility::VISIBLE, board_id));
+            return;
+        }
+
+        self.commands
+            .spawn((
+                MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(self.assets.tile.clone()),
+                    transform,
+                    material: self.assets.tile_material.clone(),
+                    ..Default::default()
+                },
+                PickableBundle::default(),
+            ))
+            .insert(HighLight)
+            .insert(board_id)
+            .with_children(|parent| {
+                parent.spawn(MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(self.assets.indicator.clone()),
+                    transform: Transform::from_xyz(0.0, 0.0, 0.01),
+                    material: self.material.0.clone(),
+                    ..Default::default()
+                });
+            });
     }
 }
 
-pub fn highlight_tile(
-    commands: &mut Commands,
-    grid_size: &TilemapGridSize,
-    map_type: &TilemapType,
-    tile_pos: &TilePos,
-    mesh: &mut Assets<Mesh>,
-    material: &mut Assets<ColorMaterial>,
+// the ghost indicator draws the selected piece on the squares it can be moved to
+fn show_ghost(
+    selected: Res<SelectedPiece>,
+    settings: Res<Settings>,
+    piece_q: Query<&Handle<Image>, With<PieceType>>,
+    highlight_material: Res<HighlightMaterial>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if settings.indicator.shape != IndicatorShape::Ghost || !selected.is_changed() {
+        return;
+    }
+
+    if let Some(Ok(image)) = selected.0.map(|e| piece_q.get(e)) {
+        if let Some(material) = materials.get_mut(&highlight_material.0) {
+            material.texture = Some(image.clone());
+        }
+    }
+}
+
+// a move frees the piece that was touched, whichever piece it was made with
+fn release_touched_piece(mut touched: ResMut<TouchedPiece>) {
+    touched.0 = None;
+}
+
+// the mesh of an indicator, centered on the origin. a bold one, for low vision, has bigger dots
+// and thicker outlines
+pub fn indicator_mesh(indicator: &Indicator, bold: bool) -> Mesh {
+    let side = TILE_SIZE * indicator.size;
+    let (radius, border) = match bold {
+        true => ((side * 0.75).min(TILE_SIZE / 2.0), side / 4.0),
+        false => (side / 2.0, side / 8.0),
+    };
+
+    match indicator.shape {
+        IndicatorShape::Dot => Mesh::from(shape::Circle::new(radius)),
+        IndicatorShape::Square | IndicatorShape::Ghost => {
+            Mesh::from(shape::Quad::new(Vec2::splat(side)))
+        }
+        IndicatorShape::Outline => outline_mesh(side, border),
+    }
+}
+
+// a square frame, the border being `width` wide
+fn outline_mesh(side: f32, width: f32) -> Mesh {
+    let (outer, inner) = (side / 2.0, side / 2.0 - width);
+    let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+
+    for (x, y) in corners {
+        positions.push([x * outer, y * outer, 0.0]);
+        positions.push([x * inner, y * inner, 0.0]);
+    }
+    // two counter clockwise triangles fill the border between a corner and the next one
+    for i in 0..4 {
+        let (o, n) = (i * 2, (i + 1) % 4 * 2);
+        indices.extend([o, n, o + 1, o + 1, n, n + 1]);
+    }
+
+    let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+    let uvs: Vec<[f32; 2]> = positions
+        .iter()
+        .map(|[x, y, _]| [x / side + 0.5, 0.5 - y / side])
+        .collect();
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+
+    mesh
+}
+
+// despawns the captured pieces, their tiles no longer refer to them by then
+fn handle_piece_death(
+    mut commands: Commands,
+    mut death_event: EventReader<PieceDeathEvent>,
+    piece_q: Query<&PieceType>,
+    mut captured_event: EventWriter<PieceCaptured>,
 ) {
-    // 2D vector with the x and y of the tile transform
-    let vec_t = tile_pos.center_in_world(grid_size, map_type);
-
-    commands
-        .spawn((
-            MaterialMesh2dBundle {
-                mesh: Mesh2dHandle(mesh.add(Mesh::from(shape::Quad::new(Vec2::splat(56.0))))),
-                transform: Transform::from_xyz(vec_t.x, vec_t.y, 0.1),
-                material: material.add(ColorMaterial::from(
-                    Color::hex("3181C6").expect("Error here"),
-                )),
-                ..Default::default()
-            },
-            PickableBundle::default(),
-        ))
-        .insert(HighLight);
-}
-
-fn handle_piece_death(mut commands: Commands, mut death_event: EventReader<PieceDeathEvent>) {
     for event in death_event.iter() {
+        if let Ok(piece_t) = piece_q.get(event.0) {
+            captured_event.send(PieceCaptured(piece_t.piece()));
+        }
+
         commands.entity(event.0).despawn_recursive();
     }
 }
@@ -0,0 +1,279 @@
+use std::{fs, marker::PhantomData, path::Path};
+
+use bevy::{ecs::system::SystemParam, prelude::*, window::FileDragAndDrop};
+use bevy_egui::{egui, EguiContext};
+use chess_core::{Board, Move, Outcome, Pgn, Piece, Team};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    board::{ChessBoard, PositionReset},
+    game::{game_pgn, DrawOffer, GameResult, GameState},
+    history::MoveHistory,
+    piece::TurnState,
+};
+
+// a game read from a PGN file, or the game just ended, stepped through on the board while it's
+// reviewed
+#[derive(Resource, Clone, Debug)]
+pub struct Playback {
+    pub pgn: Pgn,
+    // the position before every move of the game, and the one it ended in
+    positions: Vec<(Board, Team)>,
+    moves: Vec<Move>,
+    // the piece every move of the game took, if any
+    captures: Vec<Option<Piece>>,
+    // how many moves of the game the board shows
+    pub ply: usize,
+    // the ply the board was last set to, none until the game is first shown
+    shown: Option<usize>,
+    // the game reviewed is the one just ended, which the board goes back to the end of once the
+    // review is left, instead of being played on
+    finished_game: bool,
+}
+
+// the position of the reviewed game being looked at, which the main board shows in place of the
+// game played until the review is left. the game played is never touched by the review
+#[derive(Resource, Default, Debug)]
+pub struct ReviewPosition(pub Option<(Board, Team)>);
+
+impl ReviewPosition {
+    // the board shown, the one reviewed if there's one or else the one played on
+    pub fn shown<'a>(&'a self, board: &'a ChessBoard) -> &'a Board {
+        match &self.0 {
+            Some((position, _)) => position,
+            None => &board.0,
+        }
+    }
+}
+
+// the position the main board shows, the game played or the one reviewed, and the move that led
+// to it
+#[derive(SystemParam)]
+pub struct ShownPosition<'w, 's> {
+    board: Res<'w, ChessBoard>,
+    review: Res<'w, ReviewPosition>,
+    playback: Option<Res<'w, Playback>>,
+    history: Res<'w, MoveHistory>,
+    #[system_param(ignore)]
+    _marker: PhantomData<&'s ()>,
+}
+
+impl ShownPosition<'_, '_> {
+    pub fn board(&self) -> &Board {
+        self.review.shown(&self.board)
+    }
+
+    pub fn last_move(&self) -> Option<Move> {
+        match (&self.review.0, &self.playback) {
+            (Some(_), Some(playback)) => playback.last_move(),
+            _ => self.history.moves.last().map(|record| record.mv),
+        }
+    }
+}
+
+impl Playback {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let pgn = Pgn::parse(&text).map_err(|e| e.to_string())?;
+        let moves = pgn.play().map_err(|e| e.to_string())?;
+        let start = pgn.starting_position().map_err(|e| e.to_string())?;
+
+        Ok(Self::new(pgn, start, moves))
+    }
+
+    // the game just ended, shown from the position it ended in, which is the one on the board
+    pub fn from_game(history: &MoveHistory, outcome: Outcome) -> Self {
+        let start = Board::from_fen(&history.starting_fen)
+            .expect("the starting position of the game is written by the board");
+        let moves = history.moves.iter().map(|record| record.mv).collect();
+        let mut playback = Self::new(game_pgn(history, outcome), start, moves);
+
+        playback.ply = playback.len();
+        playback.shown = Some(playback.ply);
+        playback.finished_game = true;
+        playback
+    }
+
+    fn new(pgn: Pgn, start: (Board, Team), moves: Vec<Move>) -> Self {
+        let mut positions = vec![start];
+        let mut captures = Vec::new();
+        for mv in moves.iter().copied() {
+            let (mut board, to_move) = positions[positions.len() - 1].clone();
+
+            captures.push(board.make_move(mv));
+            positions.push((board, to_move.opposite()));
+        }
+
+        Self {
+            pgn,
+            positions,
+            moves,
+            captures,
+            ply: 0,
+            shown: None,
+            finished_game: false,
+        }
+    }
+
+    // the number of moves of the game
+    pub fn len(&self) -> usize {
+        self.positions.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // the move that led to the position shown, none at the start of the game
+    pub fn last_move(&self) -> Option<Move> {
+        self.ply.checked_sub(1).map(|ply| self.moves[ply])
+    }
+
+    // the pieces taken up to the position shown, in the order they were taken
+    pub fn captured(&self) -> impl Iterator<Item = Piece> + '_ {
+        self.captures[..self.ply].iter().flatten().copied()
+    }
+}
+
+pub struct PlaybackPlugin;
+
+impl Plugin for PlaybackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReviewPosition>()
+            .add_startup_system(Self::review_opened_game.run_if_resource_exists::<Playback>())
+            .add_exit_system(GameState::Review, Self::leave_review)
+            .add_system(Self::open_dropped_pgn)
+            .add_system(
+                Self::playback_controls
+                    .run_in_state(GameState::Review)
+                    .run_if_resource_exists::<Playback>(),
+            );
+    }
+}
+
+impl PlaybackPlugin {
+    // a game opened from the command line is reviewed from the start
+    fn review_opened_game(mut commands: Commands) {
+        commands.insert_resource(NextState(GameState::Review));
+    }
+
+    // the board shows the game played again, however the review was left
+    fn leave_review(mut review: ResMut<ReviewPosition>, mut reset: EventWriter<PositionReset>) {
+        review.0 = None;
+        reset.send(PositionReset);
+    }
+
+    // a .pgn file dropped on the window replaces the game, and is reviewed from its first move
+    fn open_dropped_pgn(
+        mut commands: Commands,
+        mut events: EventReader<FileDragAndDrop>,
+        mut offer: ResMut<DrawOffer>,
+    ) {
+        for event in events.iter() {
+            let path = match event {
+                FileDragAndDrop::DroppedFile { path_buf, .. } => path_buf,
+                _ => continue,
+            };
+            if path.extension().is_none_or(|extension| extension != "pgn") {
+                continue;
+            }
+
+            match Playback::load(path) {
+                Ok(playback) => {
+                    info!("Opened {} with {} moves", path.display(), playback.len());
+                    commands.insert_resource(playback);
+                    commands.remove_resource::<GameResult>();
+                    commands.insert_resource(NextState(GameState::Review));
+                    offer.0 = None;
+                }
+                Err(e) => error!("Couldn't open {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    // steps through the game with the buttons or the arrow keys, until the game is played on from
+    // the position shown, or the game just ended is gone back to. the position shown is kept
+    // apart, the game played only changes once it's played on from there
+    fn playback_controls(
+        mut commands: Commands,
+        mut egui_context: ResMut<EguiContext>,
+        keys: Res<Input<KeyCode>>,
+        mut playback: ResMut<Playback>,
+        mut review: ResMut<ReviewPosition>,
+        mut reset: EventWriter<PositionReset>,
+    ) {
+        let last = playback.len();
+        let mut ply = playback.ply;
+        let mut play_on = false;
+        let mut leave = false;
+
+        if keys.just_pressed(KeyCode::Left) {
+            ply = ply.saturating_sub(1);
+        }
+        if keys.just_pressed(KeyCode::Right) {
+            ply = (ply + 1).min(last);
+        }
+
+        egui::Window::new("Game review")
+            .anchor(egui::Align2::LEFT_TOP, egui::Vec2::new(8.0, 8.0))
+            .collapsible(false)
+            .resizable(false)
+            .show(egui_context.ctx_mut(), |ui| {
+                let white = playback.pgn.tag("White").unwrap_or("?");
+                let black = playback.pgn.tag("Black").unwrap_or("?");
+                ui.label(format!("{} - {}", white, black));
+
+                let shown = match ply {
+                    0 => "Starting position".to_string(),
+                    ply => format!("Move {} of {}: {}", ply, last, playback.pgn.moves[ply - 1]),
+                };
+                ui.label(shown);
+                if let (true, Some(result)) = (ply == last, &playback.pgn.result) {
+                    ui.label(format!("Result: {}", result));
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("|<").clicked() {
+                        ply = 0;
+                    }
+                    if ui.button("<").clicked() {
+                        ply = ply.saturating_sub(1);
+                    }
+                    if ui.button(">").clicked() {
+                        ply = (ply + 1).min(last);
+                    }
+                    if ui.button(">|").clicked() {
+                        ply = last;
+                    }
+                });
+                match playback.finished_game {
+                    true => leave = ui.button("Back").clicked(),
+                    false => play_on = ui.button("Play from here").clicked(),
+                }
+            });
+
+        // the first position is shown as soon as the game is opened
+        if playback.shown != Some(ply) {
+            playback.ply = ply;
+            playback.shown = Some(ply);
+            review.0 = Some(playback.positions[ply].clone());
+            reset.send(PositionReset);
+        }
+
+        // the game played on starts where the review stopped
+        if play_on {
+            let (position, to_move) = playback.positions[ply].clone();
+
+            commands.insert_resource(MoveHistory::new(&position, to_move));
+            commands.remove_resource::<Playback>();
+            commands.insert_resource(NextState(GameState::Playing));
+            commands.insert_resource(ChessBoard(position));
+            commands.insert_resource(TurnState(to_move));
+        }
+        // the game just ended is left as it ended, whatever position was being looked at
+        if leave {
+            commands.remove_resource::<Playback>();
+            commands.insert_resource(NextState(GameState::GameOver));
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use bevy::{
+    prelude::*,
+    winit::{UpdateMode, WinitSettings},
+};
+use iyes_loopless::prelude::*;
+
+use crate::hotkeys_enabled;
+
+// most of the time nothing moves on the board, so the game can be told to update and redraw
+// only when there is input, instead of running the full loop every frame
+pub struct PowerSavingPlugin;
+
+impl Plugin for PowerSavingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(Self::toggle_power_saving.run_if(hotkeys_enabled));
+    }
+}
+
+impl PowerSavingPlugin {
+    // switches between rendering every frame and rendering on demand when L is pressed
+    fn toggle_power_saving(keys: Res<Input<KeyCode>>, mut winit_settings: ResMut<WinitSettings>) {
+        if !keys.just_pressed(KeyCode::L) {
+            return;
+        }
+
+        if let UpdateMode::Continuous = winit_settings.focused_mode {
+            *winit_settings = WinitSettings::desktop_app();
+            info!("Power saving mode on");
+        } else {
+            *winit_settings = WinitSettings::game();
+            info!("Power saving mode off");
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use chess_core::{Move, PROMOTIONS};
+use iyes_loopless::prelude::*;
+
+use crate::{game::GameState, movement::MoveRequested, piece::PieceSystem};
+
+// the move of a pawn to the last rank, waiting for the player to pick what it turns into
+#[derive(Resource, Debug)]
+pub struct PendingPromotion(pub Move);
+
+pub struct PromotionPlugin;
+
+impl Plugin for PromotionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            Self::promotion_chooser
+                .run_in_state(GameState::Promotion)
+                .run_if_resource_exists::<PendingPromotion>()
+                .before(PieceSystem::MovePiece),
+        );
+    }
+}
+
+impl PromotionPlugin {
+    // asks which piece the pawn is promoted to, the move can also be taken back
+    fn promotion_chooser(
+        mut commands: Commands,
+        mut egui_context: ResMut<EguiContext>,
+        pending: Res<PendingPromotion>,
+        mut move_request: EventWriter<MoveRequested>,
+    ) {
+        let mut picked = None;
+        let mut cancelled = false;
+
+        egui::Window::new("Promotion")
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .collapsible(false)
+            .resizable(false)
+            .show(egui_context.ctx_mut(), |ui| {
+                ui.label("Promote the pawn to");
+                ui.horizontal(|ui| {
+                    for kind in PROMOTIONS {
+                        if ui.button(format!("{:?}", kind)).clicked() {
+                            picked = Some(kind);
+                        }
+                    }
+                });
+                cancelled = ui.button("Cancel").clicked();
+            });
+
+        if picked.is_none() && !cancelled {
+            return;
+        }
+
+        if let Some(kind) = picked {
+            move_request.send(MoveRequested(Move {
+                promotion: Some(kind),
+                ..pending.0
+            }));
+        }
+        commands.remove_resource::<PendingPromotion>();
+        commands.insert_resource(NextState(GameState::Playing));
+    }
+}
@@ -0,0 +1,111 @@
+use std::{fmt::Write, fs, time::SystemTime};
+
+use bevy::{
+    prelude::*,
+    winit::{UpdateMode, WinitSettings},
+};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    board::ChessBoard,
+    history::MoveHistory,
+    hotkeys_enabled,
+    logging::LogFile,
+    piece::TurnState,
+    save::unix_secs,
+    settings::Settings,
+    theme::{Theme, Themes},
+};
+
+// how many of the last lines of the log file go into a report
+const REPORT_LOG_LINES: usize = 200;
+
+// writes what's needed to reproduce a problem with the game to a text file when F12 is pressed,
+// so it can be attached to an issue
+pub struct ReportPlugin;
+
+impl Plugin for ReportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(Self::write_report.run_if(hotkeys_enabled));
+    }
+}
+
+impl ReportPlugin {
+    fn write_report(
+        keys: Res<Input<KeyCode>>,
+        (board, turn): (Res<ChessBoard>, Res<TurnState>),
+        history: Res<MoveHistory>,
+        settings: Res<Settings>,
+        (themes, theme_assets): (Res<Themes>, Res<Assets<Theme>>),
+        winit_settings: Res<WinitSettings>,
+        log_file: Option<Res<LogFile>>,
+    ) {
+        if !keys.just_pressed(KeyCode::F12) {
+            return;
+        }
+
+        let theme = themes
+            .handles
+            .get(themes.active)
+            .and_then(|handle| theme_assets.get(handle))
+            .map_or("not loaded", |theme| theme.name.as_str());
+        let power_saving = match winit_settings.focused_mode {
+            UpdateMode::Continuous => "off",
+            _ => "on",
+        };
+        let mut report = String::new();
+
+        // writing to a string can't fail
+        let _ = writeln!(
+            report,
+            "{} v{} ({} {}, {} build)",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            if cfg!(debug_assertions) {
+                "debug"
+            } else {
+                "release"
+            }
+        );
+        let _ = writeln!(report, "theme: {}", theme);
+        let _ = writeln!(report, "power saving: {}", power_saving);
+        let _ = writeln!(report, "settings: {:?}", *settings);
+        // the position can be set up again from its FEN, and the game replayed from where it
+        // started
+        let _ = writeln!(report, "\nposition: {}", board.0.to_fen(turn.0));
+        let _ = writeln!(report, "started from: {}", history.starting_fen);
+        let _ = writeln!(report, "moves: {}", history.san_moves().join(" "));
+
+        match log_file {
+            Some(log_file) => match fs::read_to_string(&log_file.0) {
+                Ok(log) => {
+                    let lines: Vec<&str> = log.lines().collect();
+                    let recent = &lines[lines.len().saturating_sub(REPORT_LOG_LINES)..];
+
+                    let _ = writeln!(report, "\nlast lines of {}:", log_file.0.display());
+                    for line in recent {
+                        let _ = writeln!(report, "{}", line);
+                    }
+                }
+                Err(e) => {
+                    let _ = writeln!(report, "\ncouldn't read {}: {}", log_file.0.display(), e);
+                }
+            },
+            None => {
+                let _ = writeln!(
+                    report,
+                    "\nno log recorded, start the game with --log-file <path> to add it"
+                );
+            }
+        }
+
+        let path = format!("report-{}.txt", unix_secs(SystemTime::now()));
+
+        match fs::write(&path, report) {
+            Ok(()) => info!("Problem report written to {}", path),
+            Err(e) => error!("Couldn't write the problem report: {}", e),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use bevy::{
+    prelude::{shape, Color, Component, Mesh, Name, Query, Transform, Vec2},
+    sprite::{ColorMaterial, MaterialMesh2dBundle, Mesh2dHandle, Sprite, SpriteBundle},
+    utils::default,
+};
+use bevy_ecs_tilemap::{
+    prelude::{TilemapGridSize, TilemapType},
+    tiles::{TilePos, TileStorage},
+};
+use bevy_mod_picking::PickableBundle;
+use chess_core::{Piece, PieceKind, Team};
+
+use crate::{
+    board::{BoardId, PieceSpawner, Tile, TileState},
+    piece::PieceType,
+};
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Rook {
+    pub team: Team,
+}
+
+// helper function to spawn the pieces
+pub fn spawn_piece(
+    spawner: &mut PieceSpawner,
+    piece_team: Team,
+    pos: TilePos,
+    (tile_storage, grid_size, map_type): (&TileStorage, &TilemapGridSize, &TilemapType),
+    tile_query: &mut Query<(&TilePos, &mut TileState, &BoardId)>,
+) {
+    // gets the entity of the tile in the given tile position
+    if let Some(tile_entity) = tile_storage.get(&pos) {
+        // gets the transform relative to the tile position selected
+        // and the state of the it
+        let (tile_pos, mut tile_state, board_id) = tile_query.get_mut(tile_entity).unwrap();
+        let vector_pos = tile_pos.center_in_world(grid_size, map_type);
+
+        let image = spawner.game_assets.piece_image(Piece {
+            kind: PieceKind::Rook,
+            team: piece_team,
+        });
+        let piece_ent = spawner
+            .commands
+            .spawn((SpriteBundle {
+                texture: image,
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(64.0, 64.0)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(vector_pos.x, vector_pos.y, 1.0),
+                ..default()
+            },))
+            .insert((
+                MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(
+                        spawner
+                            .meshes
+                            .add(Mesh::from(shape::Quad::new(Vec2::splat(64.0)))),
+                    ),
+                    transform: Transform::from_xyz(vector_pos.x, vector_pos.y, 0.1),
+                    material: spawner.material.add(ColorMaterial::from(Color::NONE)),
+                    ..Default::default()
+                },
+                PickableBundle::default(),
+            ))
+            .insert(PieceType::Rook(Rook { team: piece_team }))
+            .insert(*board_id)
+            .insert(Name::new("Piece"))
+            .id();
+
+        tile_state.tile_type = Tile::NotEmpty;
+        tile_state.piece_ent = Some(piece_ent);
+    }
+}
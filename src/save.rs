@@ -0,0 +1,518 @@
+use std::{
+    cmp::Reverse,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use chess_core::{is_legal, to_san, Board, Move, Team};
+use iyes_loopless::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::{ChessBoard, PositionReset},
+    game::{DrawOffer, GameResult, GameState},
+    history::{MoveHistory, MoveRecord},
+    menus_shown,
+    piece::TurnState,
+    playback::Playback,
+    promotion::PendingPromotion,
+    settings::Settings,
+};
+
+// how often the game being played is saved, so it can be resumed once the app is opened again
+const AUTOSAVE_SECS: f32 = 30.0;
+const AUTOSAVE_FILE: &str = "autosave.ron";
+// the directory of the data directory the games saved from the pause menu are kept in, a file
+// per slot
+const SLOTS_DIR: &str = "saves";
+
+// a game as it's written to disk: where it started, every move made and the position they lead
+// to, and the settings it was played with. there are no clocks to save yet. the slot name, the
+// players and when it was saved are only written for the games saved from the pause menu
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SavedGame {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub white: String,
+    #[serde(default)]
+    pub black: String,
+    // seconds since the unix epoch
+    #[serde(default)]
+    pub saved_at: u64,
+    pub starting_fen: String,
+    pub moves: Vec<SavedMove>,
+    pub position: String,
+    pub settings: Settings,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SavedMove {
+    pub uci: String,
+    // seconds since the unix epoch
+    pub played_at: u64,
+}
+
+impl SavedGame {
+    pub fn new(history: &MoveHistory, board: &Board, to_move: Team, settings: &Settings) -> Self {
+        let moves = history
+            .moves
+            .iter()
+            .map(|record| SavedMove {
+                uci: record.mv.to_uci(),
+                played_at: unix_secs(record.played_at),
+            })
+            .collect();
+
+        Self {
+            name: String::new(),
+            white: String::new(),
+            black: String::new(),
+            saved_at: 0,
+            starting_fen: history.starting_fen.clone(),
+            moves,
+            position: board.to_fen(to_move),
+            settings: settings.clone(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+        ron::from_str(&text).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+
+        fs::write(path, text).map_err(|e| e.to_string())
+    }
+
+    // plays the moves again from where the game started, so a file edited by hand can't set up a
+    // game the rules wouldn't allow, and the history gets back what's needed to take them back
+    pub fn restore(&self) -> Result<(Board, Team, MoveHistory), String> {
+        let (mut board, mut to_move) = Board::from_fen(&self.starting_fen)
+            .map_err(|e| format!("bad starting position: {}", e))?;
+        let mut history = MoveHistory::new(&board, to_move);
+
+        for (ply, saved) in self.moves.iter().enumerate() {
+            let mv = Move::from_uci(&saved.uci)
+                .filter(|mv| board.piece_at(mv.from).map(|piece| piece.team) == Some(to_move))
+                .filter(|mv| is_legal(&board, *mv))
+                .ok_or_else(|| format!("illegal move {} at ply {}", saved.uci, ply + 1))?;
+            let san = to_san(&board, mv);
+            let undo = board
+                .undo_for(mv)
+                .expect("a legal move has a piece to move");
+
+            board.make_move(mv);
+            to_move = to_move.opposite();
+
+            let mut record = MoveRecord::new(undo, san, &board, Duration::ZERO);
+            record.played_at = UNIX_EPOCH + Duration::from_secs(saved.played_at);
+            history.moves.push(record);
+        }
+
+        if board.to_fen(to_move) != self.position {
+            return Err("the moves don't lead to the position saved".to_string());
+        }
+
+        Ok((board, to_move, history))
+    }
+}
+
+// where the game keeps its saves and settings, the platform's data directory, or the working
+// directory if there's none. insert it before adding the game to keep them somewhere else
+#[derive(Resource, Clone, Debug)]
+pub struct DataDir(pub PathBuf);
+
+impl Default for DataDir {
+    fn default() -> Self {
+        Self(
+            dirs::data_dir()
+                .map(|dir| dir.join("bevy-chess"))
+                .unwrap_or_default(),
+        )
+    }
+}
+
+// the file of the slot with the given name, none if the name has nothing a file can be named by
+fn slot_path(data_dir: &Path, name: &str) -> Option<PathBuf> {
+    let file: String = name
+        .trim()
+        .chars()
+        .map(|c| match c.is_alphanumeric() || c == '-' {
+            true => c,
+            false => '_',
+        })
+        .collect();
+    if file.trim_matches('_').is_empty() {
+        return None;
+    }
+
+    Some(data_dir.join(SLOTS_DIR).join(file).with_extension("ron"))
+}
+
+// every slot saved, the last one saved first, with the files that can't be read at the end
+fn list_slots(data_dir: &Path) -> Vec<(PathBuf, Result<SavedGame, String>)> {
+    let entries = match fs::read_dir(data_dir.join(SLOTS_DIR)) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut slots: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+        .map(|path| {
+            let game = SavedGame::load(&path);
+            (path, game)
+        })
+        .collect();
+    slots.sort_by_key(|(_, game)| Reverse(game.as_ref().map_or(0, |game| game.saved_at)));
+
+    slots
+}
+
+// the seconds since the unix epoch, which the saves and the exported files are stamped with
+pub(crate) fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+// how long ago a game was saved, in words
+fn saved_ago(saved_at: u64) -> String {
+    let secs = unix_secs(SystemTime::now()).saturating_sub(saved_at);
+
+    match secs {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{} min ago", secs / 60),
+        3600..=86399 => format!("{} h ago", secs / 3600),
+        _ => format!("{} days ago", secs / 86400),
+    }
+}
+
+// the game autosaved by the last run, if it wasn't finished, which the main menu offers to resume
+#[derive(Resource, Debug)]
+pub struct LastGame(pub SavedGame);
+
+// replaces the game played by the one given
+pub struct LoadGame(pub SavedGame);
+
+// the slot the game is saved to from the pause menu and the names of its players, the slots
+// saved so far, read again whenever the menu is opened, and how the last save or load went
+#[derive(Resource, Default)]
+struct SaveMenu {
+    slot: String,
+    white: String,
+    black: String,
+    slots: Vec<(PathBuf, Result<SavedGame, String>)>,
+    status: Option<String>,
+}
+
+#[derive(Resource)]
+struct Autosave {
+    timer: Timer,
+    // what was written last, so the same game isn't written again
+    last: Option<SavedGame>,
+}
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        let data_dir = app
+            .world
+            .get_resource_or_insert_with(DataDir::default)
+            .0
+            .clone();
+        let path = data_dir.join(AUTOSAVE_FILE);
+        if path.exists() {
+            match SavedGame::load(&path) {
+                Ok(game) => {
+                    app.insert_resource(LastGame(game));
+                }
+                Err(e) => error!("Couldn't read {}: {}", path.display(), e),
+            }
+        }
+
+        app.insert_resource(Autosave {
+            timer: Timer::from_seconds(AUTOSAVE_SECS, TimerMode::Repeating),
+            last: None,
+        })
+        .init_resource::<SaveMenu>()
+        .add_event::<LoadGame>()
+        .add_exit_system(GameState::MainMenu, Self::forget_last_game)
+        .add_enter_system(GameState::GameOver, Self::remove_autosave)
+        .add_enter_system(GameState::Paused, Self::read_slots)
+        .add_system(Self::autosave.run_in_state(GameState::Playing))
+        // the history of a game started or loaded is replaced by commands, which are only applied
+        // once the update is over
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            Self::autosave_position
+                .run_in_state(GameState::Playing)
+                .run_on_event::<PositionReset>(),
+        )
+        .add_system(
+            Self::save_menu
+                .run_in_state(GameState::Paused)
+                .run_if(menus_shown),
+        )
+        .add_system(Self::load_game.run_on_event::<LoadGame>());
+    }
+}
+
+impl SavePlugin {
+    // only games with a move made are worth resuming
+    fn autosave(
+        time: Res<Time>,
+        mut autosave: ResMut<Autosave>,
+        history: Res<MoveHistory>,
+        board: Res<ChessBoard>,
+        turn: Res<TurnState>,
+        settings: Res<Settings>,
+        data_dir: Res<DataDir>,
+    ) {
+        if !autosave.timer.tick(time.delta()).just_finished() || history.moves.is_empty() {
+            return;
+        }
+
+        write_autosave(
+            &data_dir.0,
+            &mut autosave,
+            &history,
+            &board.0,
+            turn.0,
+            &settings,
+        );
+    }
+
+    // a position set up without a move being made, like a move taken back, is autosaved right
+    // away. a game taken back to its first move has nothing left to resume
+    fn autosave_position(
+        mut autosave: ResMut<Autosave>,
+        history: Res<MoveHistory>,
+        board: Res<ChessBoard>,
+        turn: Res<TurnState>,
+        settings: Res<Settings>,
+        data_dir: Res<DataDir>,
+    ) {
+        match history.moves.is_empty() {
+            true => remove_autosave_file(&data_dir.0, &mut autosave),
+            false => write_autosave(
+                &data_dir.0,
+                &mut autosave,
+                &history,
+                &board.0,
+                turn.0,
+                &settings,
+            ),
+        }
+    }
+
+    fn read_slots(mut menu: ResMut<SaveMenu>, data_dir: Res<DataDir>) {
+        menu.slots = list_slots(&data_dir.0);
+    }
+
+    // saves the game played to a named slot, or replaces it by the one of a slot, next to the
+    // pause menu. every slot shows the players, when it was saved and its position. a slot that
+    // can't be read or set up is reported there, and the game played is kept
+    fn save_menu(
+        mut egui_context: ResMut<EguiContext>,
+        history: Res<MoveHistory>,
+        (board, turn): (Res<ChessBoard>, Res<TurnState>),
+        (settings, data_dir): (Res<Settings>, Res<DataDir>),
+        mut menu: ResMut<SaveMenu>,
+        mut load: EventWriter<LoadGame>,
+    ) {
+        let menu = &mut *menu;
+        let mut save = false;
+        let mut delete = None;
+
+        egui::Window::new("Saved games")
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::Vec2::new(0.0, -32.0))
+            .collapsible(false)
+            .resizable(false)
+            .show(egui_context.ctx_mut(), |ui| {
+                egui::Grid::new("save_slot").show(ui, |ui| {
+                    ui.label("Slot");
+                    ui.text_edit_singleline(&mut menu.slot);
+                    ui.end_row();
+                    ui.label("White");
+                    ui.text_edit_singleline(&mut menu.white);
+                    ui.end_row();
+                    ui.label("Black");
+                    ui.text_edit_singleline(&mut menu.black);
+                    ui.end_row();
+                });
+                save = ui
+                    .add_enabled(
+                        slot_path(&data_dir.0, &menu.slot).is_some(),
+                        egui::Button::new("Save"),
+                    )
+                    .clicked();
+                if let Some(status) = &menu.status {
+                    ui.label(status);
+                }
+
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        for (path, game) in &menu.slots {
+                            ui.group(|ui| match game {
+                                Ok(game) => {
+                                    ui.strong(&game.name);
+                                    ui.label(format!(
+                                        "{} - {}, {} moves, saved {}",
+                                        game.white,
+                                        game.black,
+                                        game.moves.len(),
+                                        saved_ago(game.saved_at)
+                                    ));
+                                    ui.monospace(&game.position);
+                                    ui.horizontal(|ui| {
+                                        if ui.button("Load").clicked() {
+                                            load.send(LoadGame(game.clone()));
+                                        }
+                                        if ui.button("Delete").clicked() {
+                                            delete = Some(path.clone());
+                                        }
+                                    });
+                                }
+                                Err(e) => {
+                                    ui.strong(path.display().to_string());
+                                    ui.label(format!("Can't be read: {}", e));
+                                    if ui.button("Delete").clicked() {
+                                        delete = Some(path.clone());
+                                    }
+                                }
+                            });
+                        }
+                    });
+            });
+
+        if let (true, Some(path)) = (save, slot_path(&data_dir.0, &menu.slot)) {
+            let game = SavedGame {
+                name: menu.slot.trim().to_string(),
+                white: menu.white.trim().to_string(),
+                black: menu.black.trim().to_string(),
+                saved_at: unix_secs(SystemTime::now()),
+                ..SavedGame::new(&history, &board.0, turn.0, &settings)
+            };
+
+            menu.status = Some(match game.save(&path) {
+                Ok(()) => {
+                    info!("Game saved to {}", path.display());
+                    format!("Saved to {}", game.name)
+                }
+                Err(e) => {
+                    error!("Couldn't save the game to {}: {}", path.display(), e);
+                    format!("Couldn't save the game: {}", e)
+                }
+            });
+            menu.slots = list_slots(&data_dir.0);
+        }
+        if let Some(path) = delete {
+            if let Err(e) = fs::remove_file(&path) {
+                error!("Couldn't remove {}: {}", path.display(), e);
+                menu.status = Some(format!("Couldn't delete the slot: {}", e));
+            }
+            menu.slots = list_slots(&data_dir.0);
+        }
+    }
+
+    // once a game is played, the one of the last run can't be resumed anymore
+    fn forget_last_game(mut commands: Commands) {
+        commands.remove_resource::<LastGame>();
+    }
+
+    // a finished game has nothing left to resume
+    fn remove_autosave(mut autosave: ResMut<Autosave>, data_dir: Res<DataDir>) {
+        remove_autosave_file(&data_dir.0, &mut autosave);
+    }
+
+    // the game loaded is played on from where it was left, whatever was going on
+    fn load_game(
+        mut commands: Commands,
+        mut events: EventReader<LoadGame>,
+        mut board: ResMut<ChessBoard>,
+        mut turn: ResMut<TurnState>,
+        mut settings: ResMut<Settings>,
+        mut menu: ResMut<SaveMenu>,
+        mut reset: EventWriter<PositionReset>,
+    ) {
+        for LoadGame(game) in events.iter() {
+            let (position, to_move, history) = match game.restore() {
+                Ok(restored) => restored,
+                Err(e) => {
+                    error!("Couldn't load the game: {}", e);
+                    menu.status = Some(format!("The saved game is corrupt: {}", e));
+                    continue;
+                }
+            };
+
+            info!("Game loaded with {} moves", history.moves.len());
+            commands.insert_resource(history);
+            commands.remove_resource::<GameResult>();
+            commands.remove_resource::<PendingPromotion>();
+            commands.remove_resource::<Playback>();
+            commands.insert_resource(DrawOffer::default());
+            commands.insert_resource(NextState(GameState::Playing));
+            board.0 = position;
+            turn.0 = to_move;
+            if *settings != game.settings {
+                *settings = game.settings.clone();
+            }
+            // saving again goes to the slot the game was loaded from
+            menu.slot = game.name.clone();
+            menu.white = game.white.clone();
+            menu.black = game.black.clone();
+            menu.status = None;
+            reset.send(PositionReset);
+        }
+    }
+}
+
+// writes the game to the autosave file, unless it's what was written last
+fn write_autosave(
+    data_dir: &Path,
+    autosave: &mut Autosave,
+    history: &MoveHistory,
+    board: &Board,
+    to_move: Team,
+    settings: &Settings,
+) {
+    let game = SavedGame::new(history, board, to_move, settings);
+    if autosave.last.as_ref() == Some(&game) {
+        return;
+    }
+
+    let path = data_dir.join(AUTOSAVE_FILE);
+    match game.save(&path) {
+        Ok(()) => {
+            debug!("Game autosaved to {}", path.display());
+            autosave.last = Some(game);
+        }
+        Err(e) => error!("Couldn't autosave the game to {}: {}", path.display(), e),
+    }
+}
+
+fn remove_autosave_file(data_dir: &Path, autosave: &mut Autosave) {
+    let path = data_dir.join(AUTOSAVE_FILE);
+
+    autosave.last = None;
+    if path.exists() {
+        if let Err(e) = fs::remove_file(&path) {
+            error!("Couldn't remove {}: {}", path.display(), e);
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::{fs, path::Path};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use iyes_loopless::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    menus_shown,
+    save::DataDir,
+    theme::{Theme, Themes},
+};
+
+// where the settings are kept between runs, in the data directory next to the saved games
+const SETTINGS_FILE: &str = "settings.ron";
+
+// what the squares a piece can move to are marked with
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IndicatorShape {
+    Dot,
+    Square,
+    Outline,
+    // the selected piece, drawn see-through
+    Ghost,
+}
+
+// how the squares a piece can move to are marked, in the colour of the theme
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct Indicator {
+    pub shape: IndicatorShape,
+    // the width of the indicator, as a part of the width of a tile
+    pub size: f32,
+    pub opacity: f32,
+}
+
+impl Default for Indicator {
+    fn default() -> Self {
+        Self {
+            shape: IndicatorShape::Square,
+            size: 0.875,
+            opacity: 1.0,
+        }
+    }
+}
+
+// the player's preferences, any field missing from the file takes the default value
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Settings {
+    // promotes pawns to a queen without asking, for fast games
+    pub auto_queen: bool,
+    // a piece picked up that can move has to be moved, as over the board
+    pub touch_move: bool,
+    // the computer plays black
+    pub vs_computer: bool,
+    pub indicator: Indicator,
+    // low vision: the pieces are drawn bigger and the squares they can move to more boldly
+    pub low_vision: bool,
+    // the lens enlarging the board under the cursor
+    pub magnifier: bool,
+    // the side of the image the board is exported to, in pixels
+    pub export_size: u32,
+    // the squares of the last move are marked on the exported image
+    pub export_last_move: bool,
+    // the name of the theme the game is drawn with
+    pub theme: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            auto_queen: false,
+            touch_move: false,
+            vs_computer: false,
+            indicator: Indicator::default(),
+            low_vision: false,
+            magnifier: false,
+            export_size: 1024,
+            export_last_move: true,
+            theme: String::from("Classic"),
+        }
+    }
+}
+
+impl Settings {
+    // the settings saved by the last run, or the default ones if there are none or they can't be
+    // read
+    fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(SETTINGS_FILE);
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => return Self::default(),
+        };
+
+        match ron::from_str(&text) {
+            Ok(settings) => settings,
+            Err(e) => {
+                warn!(
+                    "Couldn't read {}, using the defaults: {}",
+                    path.display(),
+                    e
+                );
+                Self::default()
+            }
+        }
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let data_dir = app
+            .world
+            .get_resource_or_insert_with(DataDir::default)
+            .0
+            .clone();
+
+        app.insert_resource(Settings::load(&data_dir))
+            .add_system(Self::settings_menu.run_if(menus_shown))
+            .add_system(Self::save_settings.after(Self::settings_menu));
+    }
+}
+
+impl SettingsPlugin {
+    fn settings_menu(
+        mut egui_context: ResMut<EguiContext>,
+        mut settings: ResMut<Settings>,
+        themes: Res<Themes>,
+        theme_assets: Res<Assets<Theme>>,
+    ) {
+        let mut edited = settings.clone();
+
+        egui::Window::new("Settings")
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::Vec2::new(-8.0, -8.0))
+            .resizable(false)
+            .show(egui_context.ctx_mut(), |ui| {
+                ui.checkbox(&mut edited.auto_queen, "Always promote to a queen");
+                ui.checkbox(&mut edited.touch_move, "Touch-move");
+                ui.checkbox(&mut edited.vs_computer, "Play against the computer");
+                // the themes still loading aren't listed yet
+                egui::ComboBox::from_label("Theme (T)")
+                    .selected_text(&edited.theme)
+                    .show_ui(ui, |ui| {
+                        for theme in themes.handles.iter().filter_map(|h| theme_assets.get(h)) {
+                            ui.selectable_value(&mut edited.theme, theme.name.clone(), &theme.name);
+                        }
+                    });
+                ui.collapsing("Legal moves", |ui| {
+                    let indicator = &mut edited.indicator;
+                    egui::ComboBox::from_label("Shape")
+                        .selected_text(format!("{:?}", indicator.shape))
+                        .show_ui(ui, |ui| {
+                            for shape in [
+                                IndicatorShape::Dot,
+                                IndicatorShape::Square,
+                                IndicatorShape::Outline,
+                                IndicatorShape::Ghost,
+                            ] {
+                                ui.selectable_value(
+                                    &mut indicator.shape,
+                                    shape,
+                                    format!("{:?}", shape),
+                                );
+                            }
+                        });
+                    ui.add(egui::Slider::new(&mut indicator.size, 0.2..=1.0).text("Size"));
+                    ui.add(egui::Slider::new(&mut indicator.opacity, 0.1..=1.0).text("Opacity"));
+                });
+                ui.collapsing("Exported image", |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut edited.export_size, 256..=4096)
+                            .text("Size in pixels"),
+                    );
+                    ui.checkbox(&mut edited.export_last_move, "Mark the last move");
+                });
+                ui.collapsing("Accessibility", |ui| {
+                    ui.checkbox(&mut edited.low_vision, "Large pieces and bold highlights");
+                    ui.checkbox(&mut edited.magnifier, "Magnifier lens (M)");
+                });
+            });
+
+        // only touched when something was edited, so it's only saved then
+        if edited != *settings {
+            *settings = edited;
+        }
+    }
+
+    fn save_settings(settings: Res<Settings>, data_dir: Res<DataDir>) {
+        if !settings.is_changed() || settings.is_added() {
+            return;
+        }
+
+        let text = match ron::ser::to_string_pretty(&*settings, ron::ser::PrettyConfig::default()) {
+            Ok(text) => text,
+            Err(e) => {
+                error!("Couldn't write the settings: {}", e);
+                return;
+            }
+        };
+        let path = data_dir.0.join(SETTINGS_FILE);
+        let written = fs::create_dir_all(&data_dir.0).and_then(|()| fs::write(&path, text));
+        match written {
+            Ok(()) => info!("Settings saved to {}", path.display()),
+            Err(e) => error!("Couldn't save the settings to {}: {}", path.display(), e),
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use std::mem;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    ecs::system::SystemParam,
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use bevy_ecs_tilemap::tiles::{TileColor, TilePos};
+use bevy_egui::{egui, EguiContext};
+use iyes_loopless::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    board::is_light_tile,
+    hotkeys_enabled,
+    movement::MoveMade,
+    piece::{indicator_mesh, HighlightAssets, HighlightMaterial},
+    settings::{IndicatorShape, Settings},
+    GameAssets,
+};
+
+const DEFAULT_THEME: &str = "themes/classic.theme.ron";
+
+// how the game looks and sounds, read from the .theme.ron files found in assets/themes. only the
+// colors have to be written, the game's own pieces, silence and egui's font are used otherwise
+#[derive(TypeUuid, Debug)]
+#[uuid = "5b1f3a3c-6a2e-4c1b-9d8e-2f4c7a9e1b60"]
+pub struct Theme {
+    pub name: String,
+    pub light_tile: Color,
+    pub dark_tile: Color,
+    pub highlight: Color,
+    // the folder of the assets the piece images are taken from, named as the ones at the root
+    pub pieces: Option<String>,
+    pub sounds: ThemeSounds,
+    // the font the menus and the coordinates of the exported images are written with
+    pub font: Option<Vec<u8>>,
+}
+
+// the sounds played when a move is made, as paths of the assets
+#[derive(Deserialize, Clone, Default, Debug)]
+pub struct ThemeSounds {
+    #[serde(rename = "move")]
+    pub moved: Option<String>,
+    // the move sound is played for captures too if there's none
+    pub capture: Option<String>,
+}
+
+// how a theme is written in its file, the colors being hex strings such as "3181C6"
+#[derive(Deserialize)]
+struct ThemeFile {
+    name: String,
+    light_tile: String,
+    dark_tile: String,
+    highlight: String,
+    pieces: Option<String>,
+    #[serde(default)]
+    sounds: ThemeSounds,
+    // the path of a .ttf or .otf file, read along with the theme as egui takes the font's bytes
+    font: Option<String>,
+}
+
+#[derive(Default)]
+struct ThemeLoader;
+
+impl AssetLoader for ThemeLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let file: ThemeFile = ron::de::from_bytes(bytes)?;
+            let font = match &file.font {
+                Some(path) => Some(load_context.read_asset_bytes(path).await?),
+                None => None,
+            };
+            let theme = Theme {
+                name: file.name,
+                light_tile: Color::hex(file.light_tile)?,
+                dark_tile: Color::hex(file.dark_tile)?,
+                highlight: Color::hex(file.highlight)?,
+                pieces: file.pieces,
+                sounds: file.sounds,
+                font,
+            };
+
+            load_context.set_default_asset(LoadedAsset::new(theme));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+// the theme in use, and whether it changed since the system last looked at it
+#[derive(SystemParam)]
+struct ActiveTheme<'w, 's> {
+    themes: Res<'w, Themes>,
+    theme_assets: Res<'w, Assets<Theme>>,
+    theme_events: EventReader<'w, 's, AssetEvent<Theme>>,
+}
+
+impl ActiveTheme<'_, '_> {
+    fn get(&self) -> Option<&Theme> {
+        let handle = self.themes.handles.get(self.themes.active)?;
+
+        self.theme_assets.get(handle)
+    }
+
+    // another theme was picked, or the file of the one in use was loaded or edited
+    fn updated(&mut self) -> bool {
+        let active = match self.themes.handles.get(self.themes.active) {
+            Some(handle) => handle,
+            None => return false,
+        };
+        let active_updated = self.theme_events.iter().any(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle == active,
+            AssetEvent::Removed { .. } => false,
+        });
+
+        active_updated || self.themes.is_changed()
+    }
+}
+
+// what a theme draws: the tiles, and the highlights with the indicator they are marked with
+#[derive(SystemParam)]
+struct ThemedParts<'w, 's> {
+    highlight_material: Res<'w, HighlightMaterial>,
+    highlight_assets: Res<'w, HighlightAssets>,
+    materials: ResMut<'w, Assets<ColorMaterial>>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    tile_q: Query<'w, 's, (&'static TilePos, &'static mut TileColor)>,
+}
+
+// the sounds of the theme in use, loaded as soon as it's picked so they play without a delay
+#[derive(Resource, Default)]
+struct MoveSounds {
+    moved: Option<Handle<AudioSource>>,
+    capture: Option<Handle<AudioSource>>,
+}
+
+// every theme found at startup and the index of the one in use
+#[derive(Resource)]
+pub struct Themes {
+    pub handles: Vec<Handle<Theme>>,
+    pub active: usize,
+}
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Theme>()
+            .init_asset_loader::<ThemeLoader>()
+            .init_resource::<MoveSounds>()
+            .add_startup_system(Self::load_themes)
+            .add_system(Self::cycle_theme.run_if(hotkeys_enabled))
+            .add_system(Self::pick_theme)
+            .add_system(Self::apply_theme)
+            .add_system(Self::apply_piece_set)
+            .add_system(Self::apply_font)
+            .add_system(Self::load_sounds)
+            .add_system(Self::play_move_sound.run_on_event::<MoveMade>());
+    }
+}
+
+impl ThemePlugin {
+    fn load_themes(mut commands: Commands, asset_server: Res<AssetServer>) {
+        // the web build can't list the folder, so it only gets the default theme
+        let mut handles: Vec<Handle<Theme>> = match asset_server.load_folder("themes") {
+            Ok(handles) => handles.into_iter().map(|h| h.typed()).collect(),
+            Err(_) => vec![asset_server.load(DEFAULT_THEME)],
+        };
+        // sorted by path so the themes are always switched in the same order
+        handles.sort_by_key(|h| {
+            asset_server
+                .get_handle_path(h)
+                .map(|path| path.path().to_path_buf())
+        });
+
+        let default_theme: Handle<Theme> = asset_server.load(DEFAULT_THEME);
+        let active = handles
+            .iter()
+            .position(|h| *h == default_theme)
+            .unwrap_or(0);
+
+        commands.insert_resource(Themes { handles, active });
+    }
+
+    // switches to the next theme loaded, which is kept in the settings like one picked there
+    fn cycle_theme(
+        keys: Res<Input<KeyCode>>,
+        themes: Res<Themes>,
+        theme_assets: Res<Assets<Theme>>,
+        mut settings: ResMut<Settings>,
+    ) {
+        if !keys.just_pressed(KeyCode::T) || themes.handles.is_empty() {
+            return;
+        }
+
+        let next = &themes.handles[(themes.active + 1) % themes.handles.len()];
+        if let Some(theme) = theme_assets.get(next) {
+            settings.theme = theme.name.clone();
+        }
+    }
+
+    // uses the theme named in the settings once it's loaded, the default one is kept until then
+    // or if there's none by that name
+    fn pick_theme(
+        settings: Res<Settings>,
+        mut themes: ResMut<Themes>,
+        theme_assets: Res<Assets<Theme>>,
+        mut theme_events: EventReader<AssetEvent<Theme>>,
+    ) {
+        if theme_events.iter().count() == 0 && !settings.is_changed() {
+            return;
+        }
+
+        let picked = themes.handles.iter().position(|handle| {
+            theme_assets
+                .get(handle)
+                .is_some_and(|theme| theme.name == settings.theme)
+        });
+        if let Some(picked) = picked.filter(|picked| *picked != themes.active) {
+            themes.active = picked;
+        }
+    }
+
+    // recolors the tiles and the highlights when another theme is picked or the file of the one
+    // in use is loaded or edited, and shapes the indicator as picked in the settings
+    fn apply_theme(mut active: ActiveTheme, settings: Res<Settings>, mut parts: ThemedParts) {
+        let updated = active.updated();
+        if !updated && !settings.is_changed() {
+            return;
+        }
+
+        let theme = match active.get() {
+            Some(theme) => theme,
+            None => return,
+        };
+        if updated {
+            info!("Using the {} theme", theme.name);
+        }
+
+        for (tile_pos, mut tile_color) in parts.tile_q.iter_mut() {
+            tile_color.0 = match is_light_tile(tile_pos) {
+                true => theme.light_tile,
+                false => theme.dark_tile,
+            };
+        }
+
+        // a ghost is drawn with the colors of the piece
+        let mut color = match settings.indicator.shape {
+            IndicatorShape::Ghost => Color::WHITE,
+            _ => theme.highlight,
+        };
+        color.set_a(settings.indicator.opacity);
+        if let Some(material) = parts.materials.get_mut(&parts.highlight_material.0) {
+            material.color = color;
+            material.texture = None;
+        }
+
+        // every highlight shares the mesh, so the ones already spawned change too
+        if let Some(mesh) = parts.meshes.get_mut(&parts.highlight_assets.indicator) {
+            *mesh = indicator_mesh(&settings.indicator, settings.low_vision);
+        }
+    }
+
+    // redraws the pieces on the board and in the trays with the images of the theme
+    fn apply_piece_set(
+        mut active: ActiveTheme,
+        asset_server: Res<AssetServer>,
+        mut game_assets: ResMut<GameAssets>,
+        mut image_q: Query<&mut Handle<Image>>,
+    ) {
+        if !active.updated() {
+            return;
+        }
+        let folder = match active.get() {
+            Some(theme) => theme.pieces.clone().unwrap_or_default(),
+            None => return,
+        };
+
+        let previous = mem::replace(&mut *game_assets, GameAssets::load(&asset_server, &folder));
+        for mut image in image_q.iter_mut() {
+            let index = match previous.images().into_iter().position(|old| *old == *image) {
+                Some(index) => index,
+                None => continue,
+            };
+            let replacement = game_assets.images()[index];
+            if *replacement != *image {
+                *image = replacement.clone();
+            }
+        }
+    }
+
+    // writes the menus with the font of the theme, or egui's own if it has none
+    fn apply_font(mut active: ActiveTheme, mut egui_context: ResMut<EguiContext>) {
+        if !active.updated() {
+            return;
+        }
+        let theme = match active.get() {
+            Some(theme) => theme,
+            None => return,
+        };
+
+        let mut fonts = egui::FontDefinitions::default();
+        if let Some(font) = &theme.font {
+            fonts
+                .font_data
+                .insert(theme.name.clone(), egui::FontData::from_owned(font.clone()));
+            fonts
+                .families
+                .entry(egui::FontFamily::Proportional)
+                .or_default()
+                .insert(0, theme.name.clone());
+        }
+        egui_context.ctx_mut().set_fonts(fonts);
+    }
+
+    fn load_sounds(
+        mut active: ActiveTheme,
+        asset_server: Res<AssetServer>,
+        mut sounds: ResMut<MoveSounds>,
+    ) {
+        if !active.updated() {
+            return;
+        }
+        let theme = match active.get() {
+            Some(theme) => theme,
+            None => return,
+        };
+
+        let load =
+            |path: &Option<String>| path.as_ref().map(|path| asset_server.load(path.as_str()));
+        *sounds = MoveSounds {
+            moved: load(&theme.sounds.moved),
+            capture: load(&theme.sounds.capture),
+        };
+    }
+
+    fn play_move_sound(
+        mut move_made: EventReader<MoveMade>,
+        sounds: Res<MoveSounds>,
+        audio: Res<Audio>,
+    ) {
+        for event in move_made.iter() {
+            let sound = match event.captured {
+                Some(_) => sounds.capture.as_ref().or(sounds.moved.as_ref()),
+                None => sounds.moved.as_ref(),
+            };
+            if let Some(sound) = sound {
+                audio.play(sound.clone());
+            }
+        }
+    }
+}
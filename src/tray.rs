@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use chess_core::{Piece, Team};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    board::{PositionReset, TILE_SIZE},
+    history::MoveHistory,
+    piece::PieceCaptured,
+    playback::{Playback, ReviewPosition},
+    GameAssets,
+};
+
+// the captured pieces are drawn smaller, in rows next to the board
+const CAPTURED_SIZE: f32 = TILE_SIZE / 2.0;
+const PER_ROW: usize = 4;
+// the center of the first column, a bit to the right of the last file
+const TRAY_X: f32 = TILE_SIZE * 8.0;
+
+// the pieces captured so far, in the order they were taken
+#[derive(Resource, Default, Debug)]
+pub struct CapturedPieces(pub Vec<Piece>);
+
+// marks the sprites drawn in the tray
+#[derive(Component)]
+pub struct CapturedPiece;
+
+pub struct CapturedTrayPlugin;
+
+impl Plugin for CapturedTrayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CapturedPieces>()
+            .add_system(Self::add_to_tray.run_on_event::<PieceCaptured>())
+            // the history of a game started or loaded is replaced by commands, which are only
+            // applied once the update is over
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                Self::refill_tray.run_on_event::<PositionReset>(),
+            );
+    }
+}
+
+impl CapturedTrayPlugin {
+    fn add_to_tray(
+        mut commands: Commands,
+        mut events: EventReader<PieceCaptured>,
+        mut captured: ResMut<CapturedPieces>,
+        game_assets: Res<GameAssets>,
+    ) {
+        for PieceCaptured(piece) in events.iter() {
+            add_piece(&mut commands, &mut captured, &game_assets, *piece);
+        }
+    }
+
+    // the tray is drawn again from the captures of the moves that led to the position, the game
+    // played or the one reviewed, as the pieces missing from the board don't tell in which order
+    // they were taken, or which of them a pawn was promoted to
+    fn refill_tray(
+        mut commands: Commands,
+        history: Res<MoveHistory>,
+        review: Res<ReviewPosition>,
+        playback: Option<Res<Playback>>,
+        tray_q: Query<Entity, With<CapturedPiece>>,
+        mut captured: ResMut<CapturedPieces>,
+        game_assets: Res<GameAssets>,
+    ) {
+        for sprite in tray_q.iter() {
+            commands.entity(sprite).despawn_recursive();
+        }
+        captured.0.clear();
+
+        let taken: Vec<Piece> = match (&review.0, playback) {
+            (Some(_), Some(playback)) => playback.captured().collect(),
+            _ => history
+                .moves
+                .iter()
+                .filter_map(|record| record.captured)
+                .collect(),
+        };
+        for piece in taken {
+            add_piece(&mut commands, &mut captured, &game_assets, piece);
+        }
+    }
+}
+
+// white pieces are lined up from the bottom of the board, on white's side, and black ones from the
+// top
+fn add_piece(
+    commands: &mut Commands,
+    captured: &mut CapturedPieces,
+    game_assets: &GameAssets,
+    piece: Piece,
+) {
+    let index = captured
+        .0
+        .iter()
+        .filter(|taken| taken.team == piece.team)
+        .count();
+    let (column, row) = ((index % PER_ROW) as f32, (index / PER_ROW) as f32);
+    let y = match piece.team {
+        Team::White => -CAPTURED_SIZE / 2.0 + row * CAPTURED_SIZE,
+        Team::Black => TILE_SIZE * 7.0 + CAPTURED_SIZE / 2.0 - row * CAPTURED_SIZE,
+    };
+
+    commands.spawn((
+        SpriteBundle {
+            texture: game_assets.piece_image(piece),
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(CAPTURED_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_xyz(TRAY_X + column * CAPTURED_SIZE, y, 1.0),
+            ..default()
+        },
+        CapturedPiece,
+        Name::new("Captured piece"),
+    ));
+    captured.0.push(piece);
+}
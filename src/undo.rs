@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    board::{ChessBoard, PositionReset},
+    game::{DrawOffer, GameState},
+    history::MoveHistory,
+    movement::{MoveMade, MoveRequested},
+    piece::{PieceSystem, TurnState},
+};
+
+// asks for the last move made to be taken back
+pub struct TakeBack;
+
+// asks for the last move taken back to be made again
+pub struct RedoMove;
+
+pub struct UndoPlugin;
+
+impl Plugin for UndoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TakeBack>()
+            .add_event::<RedoMove>()
+            .add_system(Self::undo_shortcut.run_in_state(GameState::Playing))
+            .add_system(
+                Self::take_back
+                    .run_in_state(GameState::Playing)
+                    .run_on_event::<TakeBack>(),
+            )
+            .add_system(
+                Self::redo
+                    .run_in_state(GameState::Playing)
+                    .run_on_event::<RedoMove>()
+                    .before(PieceSystem::MovePiece),
+            )
+            .add_system(
+                Self::follow_redo_line
+                    .run_on_event::<MoveMade>()
+                    .after(PieceSystem::MovePiece),
+            );
+    }
+}
+
+impl UndoPlugin {
+    // ctrl+z takes the last move back, and ctrl+y or ctrl+shift+z makes it again
+    fn undo_shortcut(
+        keys: Res<Input<KeyCode>>,
+        mut take_back: EventWriter<TakeBack>,
+        mut redo: EventWriter<RedoMove>,
+    ) {
+        let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+        let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+
+        if !ctrl {
+            return;
+        }
+        if keys.just_pressed(KeyCode::Y) || (shift && keys.just_pressed(KeyCode::Z)) {
+            redo.send(RedoMove);
+        } else if keys.just_pressed(KeyCode::Z) {
+            take_back.send(TakeBack);
+        }
+    }
+
+    // puts the board back as it was before the last move, and gives the turn back to the team
+    // that made it. the pieces are spawned again from the board, as the one captured is gone
+    fn take_back(
+        mut events: EventReader<TakeBack>,
+        mut history: ResMut<MoveHistory>,
+        mut board: ResMut<ChessBoard>,
+        mut turn: ResMut<TurnState>,
+        mut offer: ResMut<DrawOffer>,
+        mut reset: EventWriter<PositionReset>,
+    ) {
+        for _ in events.iter() {
+            let undo = match history.moves.pop() {
+                Some(record) => record.undo,
+                None => continue,
+            };
+
+            history.redos.push(undo.mv);
+            board.0.undo_move(undo);
+            turn.0 = undo.piece.team;
+            offer.0 = None;
+            info!(from = ?undo.mv.from, to = ?undo.mv.to, "move taken back");
+            reset.send(PositionReset);
+        }
+    }
+
+    // the move is made again as any other, and leaves the redo line once it's made
+    fn redo(
+        events: EventReader<RedoMove>,
+        history: Res<MoveHistory>,
+        mut move_request: EventWriter<MoveRequested>,
+    ) {
+        events.clear();
+
+        if let Some(mv) = history.redos.last() {
+            move_request.send(MoveRequested(*mv));
+        }
+    }
+
+    // making the move on top of the redo line goes on with the line taken back, any other move starts
+    // a new one
+    fn follow_redo_line(mut events: EventReader<MoveMade>, mut history: ResMut<MoveHistory>) {
+        for event in events.iter() {
+            match history.redos.last() == Some(&event.mv) {
+                true => {
+                    history.redos.pop();
+                }
+                false => history.redos.clear(),
+            }
+        }
+    }
+}
@@ -1,5 +1,16 @@
-use bevy::{prelude::*, render::settings::WgpuSettings, winit::WinitPlugin};
+use std::{env, fs, path::PathBuf};
 
+use bevy::{
+    log::LogPlugin,
+    prelude::*,
+    render::settings::WgpuSettings,
+    window::WindowId,
+    winit::{WinitPlugin, WinitSettings},
+};
+
+// the engine without a renderer or a window, so the systems of a test run as they would in the
+// game. logging is left out, as the global logger can only be set once per process and every
+// test builds its own app
 pub(crate) fn bevy_test_app() -> App {
     let mut app = App::new();
 
@@ -7,7 +18,33 @@ pub(crate) fn bevy_test_app() -> App {
         backends: None,
         ..Default::default()
     })
-    .add_plugins(DefaultPlugins.build().disable::<WinitPlugin>());
+    .add_plugins(
+        DefaultPlugins
+            .build()
+            .disable::<WinitPlugin>()
+            .disable::<LogPlugin>(),
+    );
+
+    // winit isn't there to open the window the menus are drawn in, or to add the settings the
+    // game switches for power saving, so both are made up
+    app.insert_resource(WinitSettings::game());
+    app.world.resource_mut::<Windows>().add(Window::new(
+        WindowId::primary(),
+        &WindowDescriptor::default(),
+        800,
+        600,
+        1.0,
+        None,
+        None,
+    ));
 
     app
 }
+
+// an empty directory of its own for the test with the given name to write to
+pub(crate) fn test_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("bevy-chess-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    dir
+}
@@ -1,24 +1,152 @@
+use std::{fs, thread, time::Duration};
+
 use anyhow::Result;
 use bevy::prelude::*;
-use bevy_chess::*;
+use bevy_chess::prelude::*;
+use bevy_ecs_tilemap::tiles::{TileColor, TilePos};
+use bevy_mod_picking::DefaultPickingPlugins;
+use iyes_loopless::prelude::*;
 
 mod common;
 
-#[test]
-fn spawns_entity_with_name() -> Result<()> {
+// the whole game, keeping its saves and settings in a directory of the test's own. the tilemap
+// is only rendered by TilemapPlugin, which needs a renderer, its components work without it
+fn chess_app(name: &str) -> App {
     let mut app = common::bevy_test_app();
-    app.add_plugin(BevyChessPlugin);
+    app.insert_resource(DataDir(common::test_dir(name)))
+        .add_plugins(DefaultPickingPlugins)
+        .add_plugin(BevyChessPlugin);
+
+    app
+}
+
+#[test]
+fn sets_up_the_starting_position() -> Result<()> {
+    let mut app = chess_app("starting-position");
 
     app.update();
 
-    let e = app
+    let pieces = app
         .world
-        .query_filtered::<Entity, With<BevyChessComponent>>()
+        .query_filtered::<&BoardId, With<PieceType>>()
         .iter(&app.world)
-        .next()
-        .unwrap();
+        .filter(|board_id| **board_id == BoardId::MAIN)
+        .count();
+    let fen = app
+        .world
+        .resource::<ChessBoard>()
+        .0
+        .to_fen(app.world.resource::<TurnState>().0);
+
+    assert_eq!(pieces, 32);
+    assert_eq!(fen, STARTING_FEN);
+
+    Ok(())
+}
+
+#[test]
+fn moves_requested_are_made_and_recorded() -> Result<()> {
+    let mut app = chess_app("moves-requested");
+    let mv = Move::from_uci("e2e4").ok_or_else(|| anyhow::anyhow!("e2e4 isn't a move"))?;
+
+    // moves are only made while the game is played, not from the main menu the game opens on
+    app.update();
+    app.world.insert_resource(NextState(GameState::Playing));
+    app.update();
+    app.world.send_event(MoveRequested(mv));
+    app.update();
+
+    let history = app.world.resource::<MoveHistory>();
+    assert_eq!(history.san_moves(), ["e4"]);
+    assert_eq!(app.world.resource::<TurnState>().0, Team::Black);
+
+    fs::remove_dir_all(&app.world.resource::<DataDir>().0).ok();
+    Ok(())
+}
+
+#[test]
+fn the_computer_answers_draw_offers() -> Result<()> {
+    let mut app = chess_app("draw-offers");
+
+    app.update();
+    app.world.resource_mut::<Settings>().vs_computer = true;
+    app.world.insert_resource(NextState(GameState::Playing));
+    app.update();
+    // the position is even, so the computer has nothing to play for
+    app.world.resource_mut::<DrawOffer>().0 = Some(Team::White);
+    app.update();
+
+    let result = app.world.resource::<GameResult>();
+    assert_eq!(result.0, Outcome::DrawAgreed);
+    assert_eq!(app.world.resource::<DrawOffer>().0, None);
+
+    fs::remove_dir_all(&app.world.resource::<DataDir>().0).ok();
+    Ok(())
+}
+
+#[test]
+fn low_vision_draws_the_pieces_bigger() -> Result<()> {
+    let mut app = chess_app("low-vision");
+
+    app.update();
+    app.world.resource_mut::<Settings>().low_vision = true;
+    app.update();
+
+    let mut sprites = app
+        .world
+        .query_filtered::<&Sprite, With<PieceType>>()
+        .iter(&app.world)
+        .map(|sprite| sprite.custom_size)
+        .collect::<Vec<_>>();
+    sprites.dedup();
+    assert_eq!(sprites, [Some(Vec2::splat(80.0))]);
+
+    fs::remove_dir_all(&app.world.resource::<DataDir>().0).ok();
+    Ok(())
+}
+
+#[test]
+fn the_overlay_starts_the_game_without_the_menus() -> Result<()> {
+    let mut app = common::bevy_test_app();
+    app.insert_resource(DataDir(common::test_dir("overlay")))
+        .insert_resource(Overlay)
+        .add_plugins(DefaultPickingPlugins)
+        .add_plugin(BevyChessPlugin);
+
+    app.update();
+
+    let state = app.world.resource::<CurrentState<GameState>>();
+    assert_eq!(state.0, GameState::Playing);
+
+    fs::remove_dir_all(&app.world.resource::<DataDir>().0).ok();
+    Ok(())
+}
+
+#[test]
+fn the_theme_picked_in_the_settings_colors_the_board() -> Result<()> {
+    let mut app = chess_app("theme");
+    let dark_tile = Color::hex("B58863")?;
+
+    app.update();
+    app.world.resource_mut::<Settings>().theme = String::from("Wood");
 
-    assert_eq!(app.world.query::<&Name>().get(&app.world, e)?.as_str(), "BevyChessPlugin Root");
+    // the themes are read from their files in the background
+    let mut a1_color = None;
+    for _ in 0..500 {
+        app.update();
+        a1_color = app
+            .world
+            .query::<(&TilePos, &TileColor)>()
+            .iter(&app.world)
+            .find(|(tile_pos, _)| **tile_pos == TilePos { x: 0, y: 0 })
+            .map(|(_, tile_color)| tile_color.0);
+        if a1_color == Some(dark_tile) {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(a1_color, Some(dark_tile));
 
+    fs::remove_dir_all(&app.world.resource::<DataDir>().0).ok();
     Ok(())
 }